@@ -1,11 +1,62 @@
-use caller_utils::{TodoState, TodoItem};
-use caller_utils::todo::{export_state_local_rpc, import_state_local_rpc};
+use caller_utils::{TodoErrorCode, TodoState, TodoItem};
+use caller_utils::todo::{
+    add_subtask_local_rpc, clear_completed_local_rpc, delete_task_local_rpc,
+    empty_trash_local_rpc, export_full_local_rpc, export_state_local_rpc,
+    export_tasks_local_rpc, import_full_local_rpc, import_state_local_rpc,
+    import_tasks_local_rpc, merge_tasks_local_rpc, reorder_tasks_local_rpc,
+    restore_task_local_rpc, search_tasks_local_rpc, set_collapse_whitespace_local_rpc,
+    set_list_title_local_rpc, set_reject_duplicates_local_rpc, set_shared_local_rpc,
+    set_strict_remote_validation_local_rpc, set_text_format_local_rpc, share_tasks_local_rpc,
+    stats_local_rpc, toggle_many_local_rpc,
+};
 // Add this import here, as fail! is expanded in this file
 use crate::hyperware::process::tester::{FailResponse, Response as TesterResponse};
 
 use serde_json; 
 mod tester_lib;
 
+// Pending task with default metadata, for seeding the app under test
+fn item(id: &str, text: &str) -> TodoItem {
+    TodoItem {
+        id: id.to_string(),
+        text: text.to_string(),
+        completed: false,
+        completed_at: None,
+        icon: None,
+        updated_at: 0,
+        priority: 1,
+        due_date: None,
+        start_date: None,
+        tags: vec![],
+        shared: true,
+        estimate_mins: None,
+        parent_id: None,
+        recurrence: None,
+    }
+}
+
+// Empty the list and trash (and reset settings) so a test starts clean
+async fn reset_tasks(address: &Address) {
+    let empty = r#"{ "schema_version": 1, "tasks": [] }"#;
+    match import_full_local_rpc(address, empty.to_string()).await {
+        Ok(Ok(())) => {}
+        other => {
+            fail!(format!("Failed to clear tasks: {:?}", other));
+        }
+    }
+}
+
+// Add `tasks` to the list through import_tasks
+async fn seed_tasks(address: &Address, tasks: Vec<TodoItem>) {
+    let doc = serde_json::json!({ "version": 1, "exported_at": 0, "tasks": tasks }).to_string();
+    match import_tasks_local_rpc(address, doc).await {
+        Ok(Ok(_)) => {}
+        other => {
+            fail!(format!("Failed to seed tasks: {:?}", other));
+        }
+    }
+}
+
 
 async_test_suite!(
     "todo-test-template-dot-os-v0",
@@ -24,8 +75,8 @@ async_test_suite!(
         // 1. Define initial state (dummy data)
         let initial_state = TodoState {
             tasks: vec![
-                TodoItem { id: "1".to_string(), text: "Task 1".to_string(), completed: false },
-                TodoItem { id: "2".to_string(), text: "Task 2".to_string(), completed: true },
+                TodoItem { id: "1".to_string(), text: "Task 1".to_string(), completed: false, completed_at: None, icon: None, updated_at: 0, priority: 1, due_date: None, start_date: None, tags: vec![], shared: true, estimate_mins: None, parent_id: None, recurrence: None },
+                TodoItem { id: "2".to_string(), text: "Task 2".to_string(), completed: true, completed_at: Some(1_700_000_000), icon: Some("🛒".to_string()), updated_at: 0, priority: 2, due_date: Some(1_700_000_000), start_date: None, tags: vec!["errands".to_string()], shared: false, estimate_mins: Some(30), parent_id: None, recurrence: None },
             ],
         };
         print_to_terminal(0, &format!("Initial state: {:?}", initial_state));
@@ -86,7 +137,11 @@ async_test_suite!(
         for (initial_task, exported_task) in initial_state.tasks.iter().zip(exported_data.tasks.iter()) {
             if initial_task.id != exported_task.id ||
                initial_task.text != exported_task.text ||
-               initial_task.completed != exported_task.completed {
+               initial_task.completed != exported_task.completed ||
+               initial_task.icon != exported_task.icon ||
+               initial_task.priority != exported_task.priority ||
+               initial_task.due_date != exported_task.due_date ||
+               initial_task.tags != exported_task.tags {
                 fail!(format!(
                     "Task mismatch detected.\nExpected Task: {:?}\nGot Task: {:?}",
                     initial_task, // Assumes TodoItem derives Debug
@@ -99,4 +154,820 @@ async_test_suite!(
         Ok(())
     },
 
+    // Lenient mode skips invalid peer tasks, strict mode rejects the batch
+    test_remote_validation_modes: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        if let Err(e) = set_strict_remote_validation_local_rpc(&address, false).await {
+            fail!(format!("set_strict_remote_validation failed: {:?}", e));
+        }
+        let batch = vec![item("lenient-ok", "Valid task"), item("lenient-bad", "   ")];
+        match merge_tasks_local_rpc(&address, batch).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("Lenient merge should succeed, got {:?}", other));
+            }
+        }
+        let tasks = match share_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                fail!(format!("share_tasks failed: {:?}", e));
+            }
+        };
+        if !tasks.iter().any(|t| t.id == "lenient-ok") {
+            fail!("Lenient merge dropped the valid task");
+        }
+        if tasks.iter().any(|t| t.id == "lenient-bad") {
+            fail!("Lenient merge kept the invalid task");
+        }
+
+        if let Err(e) = set_strict_remote_validation_local_rpc(&address, true).await {
+            fail!(format!("set_strict_remote_validation failed: {:?}", e));
+        }
+        let batch = vec![item("strict-ok", "Valid task"), item("strict-bad", "")];
+        match merge_tasks_local_rpc(&address, batch).await {
+            Ok(Err(_)) => {}
+            other => {
+                fail!(format!("Strict merge should be rejected, got {:?}", other));
+            }
+        }
+        let tasks = match share_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                fail!(format!("share_tasks failed: {:?}", e));
+            }
+        };
+        if tasks.iter().any(|t| t.id == "strict-ok") {
+            fail!("Strict merge applied part of a rejected batch");
+        }
+
+        let _ = set_strict_remote_validation_local_rpc(&address, false).await;
+        Ok(())
+    },
+
+    // Merging overlapping batches must not duplicate ids
+    test_merge_dedups_by_id: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        let first = vec![item("merge-a", "Task A"), item("merge-b", "Task B")];
+        let mut completed_b = item("merge-b", "Task B");
+        completed_b.completed = true;
+        let second = vec![completed_b, item("merge-c", "Task C")];
+
+        for batch in [first, second] {
+            match merge_tasks_local_rpc(&address, batch).await {
+                Ok(Ok(())) => {}
+                other => {
+                    fail!(format!("merge_tasks failed: {:?}", other));
+                }
+            }
+        }
+
+        let tasks = match share_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                fail!(format!("share_tasks failed: {:?}", e));
+            }
+        };
+        let mut ids: Vec<&String> = tasks.iter().map(|t| &t.id).collect();
+        let total = ids.len();
+        ids.sort();
+        ids.dedup();
+        if ids.len() != total {
+            fail!(format!("Duplicate ids after merge: {:?}", tasks));
+        }
+        for id in ["merge-a", "merge-b", "merge-c"] {
+            if !tasks.iter().any(|t| t.id == id) {
+                fail!(format!("Task {} missing after merge", id));
+            }
+        }
+        if !tasks.iter().any(|t| t.id == "merge-b" && t.completed) {
+            fail!("Completed copy of merge-b should win the merge");
+        }
+
+        Ok(())
+    },
+
+    // A v0 backup (no schema marker fields on items) upgrades to the current schema
+    test_v0_state_migrates: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        let v0 = r#"{
+            "schema_version": 0,
+            "tasks": [{ "id": "v0-1", "text": "Old task", "completed": false }]
+        }"#;
+        match import_full_local_rpc(&address, v0.to_string()).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("import_full of v0 state failed: {:?}", other));
+            }
+        }
+
+        let exported = match export_full_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => json,
+            other => {
+                fail!(format!("export_full failed: {:?}", other));
+            }
+        };
+        let state: serde_json::Value = serde_json::from_str(&exported)?;
+        if state["schema_version"] != serde_json::json!(1) {
+            fail!(format!("Expected schema version 1, got {}", state["schema_version"]));
+        }
+        let task = &state["tasks"][0];
+        if task["id"] != "v0-1" || task["priority"] != 1 || !task["due_date"].is_null() {
+            fail!(format!("v0 task not upgraded with defaults: {}", task));
+        }
+
+        Ok(())
+    },
+
+    // A backup with invalid tasks or duplicate ids is refused as a whole
+    test_import_full_validates: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        seed_tasks(&address, vec![item("keep-a", "Survives")]).await;
+
+        let duplicated = serde_json::json!({
+            "schema_version": 1,
+            "tasks": [item("dup-id", "One"), item("dup-id", "Two")],
+        })
+        .to_string();
+        match import_full_local_rpc(&address, duplicated).await {
+            Ok(Err(e)) if e.code == TodoErrorCode::InvalidId => {}
+            other => {
+                fail!(format!("Duplicate ids should be InvalidId, got {:?}", other));
+            }
+        }
+
+        let mut bad = item("bad-priority", "Too urgent");
+        bad.priority = 9;
+        let invalid = serde_json::json!({ "schema_version": 1, "tasks": [bad] }).to_string();
+        match import_full_local_rpc(&address, invalid).await {
+            Ok(Err(e)) if e.code == TodoErrorCode::InvalidInput => {}
+            other => {
+                fail!(format!("Invalid task should be InvalidInput, got {:?}", other));
+            }
+        }
+
+        match search_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(tasks)) if tasks.len() == 1 && tasks[0].id == "keep-a" => {}
+            other => {
+                fail!(format!("Rejected backup changed the list: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Imported tasks get the same icon and recurrence limits as the setters
+    test_import_rejects_bad_icon_and_recurrence: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        let mut long_icon = item("long-icon", "Decorated");
+        long_icon.icon = Some("123456789".to_string());
+        let mut zero_recurrence = item("zero-recurrence", "Every instant");
+        zero_recurrence.recurrence = Some(0);
+        for bad in [long_icon, zero_recurrence] {
+            let id = bad.id.clone();
+            let doc = serde_json::json!({ "version": 1, "exported_at": 0, "tasks": [bad] }).to_string();
+            match import_tasks_local_rpc(&address, doc).await {
+                Ok(Err(e)) if e.code == TodoErrorCode::InvalidInput => {}
+                other => {
+                    fail!(format!("Task {} should be rejected, got {:?}", id, other));
+                }
+            }
+        }
+
+        match search_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(tasks)) if tasks.is_empty() => {}
+            other => {
+                fail!(format!("Rejected imports changed the list: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // In markdown mode only bold, italic and http(s) links survive; other
+    // markup is reduced to its text
+    test_markdown_sanitized: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        seed_tasks(&address, vec![item("md-parent", "Notes")]).await;
+        match set_text_format_local_rpc(&address, "markdown".to_string()).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("set_text_format failed: {:?}", other));
+            }
+        }
+
+        let text = "## **Buy** <b>milk</b> `now` ~~later~~ [shop](javascript:alert(1)) [site](https://example.com)";
+        let args = ("md-parent".to_string(), text.to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Ok(task)) if task.text == "**Buy** milk now later shop [site](https://example.com)" => {}
+            other => {
+                fail!(format!("Markdown not sanitized: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Imported text is sanitized like typed text: import_tasks under the
+    // list's markdown mode, import_full under the backup's own mode
+    test_markdown_sanitized_on_import: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+        let text = "<b>Pay</b> [bill](javascript:alert(1))";
+
+        reset_tasks(&address).await;
+        match set_text_format_local_rpc(&address, "markdown".to_string()).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("set_text_format failed: {:?}", other));
+            }
+        }
+        seed_tasks(&address, vec![item("md-import", text)]).await;
+        match search_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(tasks)) if tasks.len() == 1 && tasks[0].text == "Pay bill" => {}
+            other => {
+                fail!(format!("import_tasks kept raw markup: {:?}", other));
+            }
+        }
+
+        let backup = serde_json::json!({
+            "schema_version": 1,
+            "text_format": "Markdown",
+            "tasks": [item("md-full", text)],
+            "trash": [item("md-trash", text)],
+        })
+        .to_string();
+        match import_full_local_rpc(&address, backup).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("import_full failed: {:?}", other));
+            }
+        }
+        match export_full_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => {
+                let full: serde_json::Value = serde_json::from_str(&json).unwrap();
+                let texts: Vec<&str> = ["tasks", "trash"]
+                    .iter()
+                    .flat_map(|list| full[*list].as_array().unwrap())
+                    .filter_map(|t| t["text"].as_str())
+                    .collect();
+                if texts != ["Pay bill", "Pay bill"] {
+                    fail!(format!("import_full kept raw markup: {:?}", texts));
+                }
+            }
+            other => {
+                fail!(format!("export_full failed: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Whitespace runs are kept by default and become single spaces once
+    // collapsing is on
+    test_collapse_whitespace: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        seed_tasks(&address, vec![item("ws-parent", "Notes")]).await;
+
+        let args = ("ws-parent".to_string(), "Pack\t\tbags  now".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Ok(task)) if task.text == "Pack\t\tbags  now" => {}
+            other => {
+                fail!(format!("Whitespace changed with collapsing off: {:?}", other));
+            }
+        }
+
+        match set_collapse_whitespace_local_rpc(&address, true).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("set_collapse_whitespace failed: {:?}", other));
+            }
+        }
+        let args = ("ws-parent".to_string(), "  Pack\t\tbags\n\nnow  ".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Ok(task)) if task.text == "Pack bags now" => {}
+            other => {
+                fail!(format!("Whitespace not collapsed: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // An empty list still exports as a complete document
+    test_export_tasks_empty: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        let exported = match export_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => json,
+            other => {
+                fail!(format!("export_tasks failed: {:?}", other));
+            }
+        };
+        let doc: serde_json::Value = serde_json::from_str(&exported)?;
+        if doc["version"] != serde_json::json!(1) || !doc["exported_at"].is_u64() {
+            fail!(format!("Export header missing: {}", doc));
+        }
+        if doc["tasks"] != serde_json::json!([]) {
+            fail!(format!("Expected empty tasks array, got {}", doc["tasks"]));
+        }
+
+        Ok(())
+    },
+
+    // Re-importing the same document adds nothing; a newer version is refused
+    test_import_tasks_dedups: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        let doc = serde_json::json!({
+            "version": 1,
+            "exported_at": 0,
+            "tasks": [item("imp-a", "First"), item("imp-b", "Second")],
+        })
+        .to_string();
+        match import_tasks_local_rpc(&address, doc.clone()).await {
+            Ok(Ok(2)) => {}
+            other => {
+                fail!(format!("Expected 2 imported tasks, got {:?}", other));
+            }
+        }
+        match import_tasks_local_rpc(&address, doc).await {
+            Ok(Ok(0)) => {}
+            other => {
+                fail!(format!("Re-import should add nothing, got {:?}", other));
+            }
+        }
+
+        let future = r#"{ "version": 999, "exported_at": 0, "tasks": [] }"#;
+        match import_tasks_local_rpc(&address, future.to_string()).await {
+            Ok(Err(e)) if e.code == TodoErrorCode::Conflict => {}
+            other => {
+                fail!(format!("Newer document version should be a Conflict, got {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // The list title is trimmed, length-checked and carried in the full backup
+    test_list_title: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        if let Ok(Ok(())) = set_list_title_local_rpc(&address, "x".repeat(101)).await {
+            fail!("Overlong list title should be rejected");
+        }
+        match set_list_title_local_rpc(&address, "  Q3 Roadmap ".to_string()).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("set_list_title failed: {:?}", other));
+            }
+        }
+
+        let exported = match export_full_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => json,
+            other => {
+                fail!(format!("export_full failed: {:?}", other));
+            }
+        };
+        let state: serde_json::Value = serde_json::from_str(&exported)?;
+        if state["list_title"] != "Q3 Roadmap" {
+            fail!(format!("Expected list title in export, got {}", state["list_title"]));
+        }
+
+        Ok(())
+    },
+
+    // Only completed tasks are removed; pending ones stay in order
+    test_clear_completed: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        let mut done_a = item("clear-done-a", "Done A");
+        done_a.completed = true;
+        let mut done_b = item("clear-done-b", "Done B");
+        done_b.completed = true;
+        seed_tasks(
+            &address,
+            vec![done_a, item("clear-open-a", "Open A"), done_b, item("clear-open-b", "Open B")],
+        )
+        .await;
+
+        match clear_completed_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(2)) => {}
+            other => {
+                fail!(format!("Expected 2 completed tasks removed, got {:?}", other));
+            }
+        }
+
+        let exported = match export_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => json,
+            other => {
+                fail!(format!("export_tasks failed: {:?}", other));
+            }
+        };
+        let doc: serde_json::Value = serde_json::from_str(&exported)?;
+        let ids: Vec<&str> = doc["tasks"]
+            .as_array()
+            .map(|tasks| tasks.iter().filter_map(|t| t["id"].as_str()).collect())
+            .unwrap_or_default();
+        if ids != ["clear-open-a", "clear-open-b"] {
+            fail!(format!("Expected only pending tasks to remain, got {:?}", ids));
+        }
+
+        Ok(())
+    },
+
+    // Private tasks never appear in what peers receive from share_tasks
+    test_private_tasks_not_shared: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        let mut private = item("vis-private", "Private task");
+        private.shared = false;
+        seed_tasks(&address, vec![item("vis-shared", "Shared task"), private]).await;
+
+        let shared = match share_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                fail!(format!("share_tasks failed: {:?}", e));
+            }
+        };
+        if shared.iter().any(|t| t.id == "vis-private")
+            || !shared.iter().any(|t| t.id == "vis-shared")
+        {
+            fail!(format!("share_tasks exposed the wrong tasks: {:?}", shared));
+        }
+
+        // Making the shared task private hides it as well
+        match set_shared_local_rpc(&address, "vis-shared".to_string(), false).await {
+            Ok(Ok(task)) if !task.shared => {}
+            other => {
+                fail!(format!("set_shared failed: {:?}", other));
+            }
+        }
+        let shared = match share_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                fail!(format!("share_tasks failed: {:?}", e));
+            }
+        };
+        if !shared.is_empty() {
+            fail!(format!("Expected no shared tasks, got {:?}", shared));
+        }
+
+        Ok(())
+    },
+
+    // Search ignores case in both the query and the task text
+    test_search_case_insensitive: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        seed_tasks(
+            &address,
+            vec![
+                item("search-a", "Buy MILK"),
+                item("search-b", "Walk the dog"),
+                item("search-c", "milkshake run"),
+            ],
+        )
+        .await;
+
+        let found = match search_tasks_local_rpc(&address, "  Milk ".to_string()).await {
+            Ok(Ok(tasks)) => tasks,
+            other => {
+                fail!(format!("search_tasks failed: {:?}", other));
+            }
+        };
+        let ids: Vec<&str> = found.iter().map(|t| t.id.as_str()).collect();
+        if ids != ["search-a", "search-c"] {
+            fail!(format!("Expected both milk tasks in order, got {:?}", ids));
+        }
+
+        match search_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(tasks)) if tasks.len() == 3 => {}
+            other => {
+                fail!(format!("Empty query should return all tasks, got {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Listed ids go first, unknown ids are ignored, the rest keep their order
+    test_reorder_tasks: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+
+        seed_tasks(
+            &address,
+            vec![item("a", "A"), item("b", "B"), item("c", "C"), item("d", "D")],
+        )
+        .await;
+
+        let order = vec!["c".to_string(), "missing".to_string(), "a".to_string()];
+        match reorder_tasks_local_rpc(&address, order).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("reorder_tasks failed: {:?}", other));
+            }
+        }
+
+        let tasks = match search_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(tasks)) => tasks,
+            other => {
+                fail!(format!("search_tasks failed: {:?}", other));
+            }
+        };
+        let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        if ids != ["c", "a", "b", "d"] {
+            fail!(format!("Expected order c, a, b, d, got {:?}", ids));
+        }
+
+        Ok(())
+    },
+
+    // Subtasks point at their parent; an unknown parent is NotFound
+    test_add_subtask: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        seed_tasks(&address, vec![item("sub-parent", "Plan trip")]).await;
+
+        let args = ("sub-parent".to_string(), "Book flights".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Ok(task)) if task.parent_id.as_deref() == Some("sub-parent") && !task.completed => {}
+            other => {
+                fail!(format!("add_subtask failed: {:?}", other));
+            }
+        }
+
+        let args = ("no-such-parent".to_string(), "Orphan".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Err(e)) if e.code == TodoErrorCode::NotFound => {}
+            other => {
+                fail!(format!("Unknown parent should be NotFound, got {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Deleted tasks can be restored, and a peer merge can't bring them back
+    test_trash_and_restore: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        seed_tasks(&address, vec![item("trash-a", "Mistake")]).await;
+
+        match delete_task_local_rpc(&address, "trash-a".to_string()).await {
+            Ok(Ok(task)) if task.id == "trash-a" => {}
+            other => {
+                fail!(format!("delete_task failed: {:?}", other));
+            }
+        }
+
+        // A newer copy from a peer is ignored while the task is in the trash
+        let mut newer = item("trash-a", "Mistake");
+        newer.updated_at = u64::MAX;
+        match merge_tasks_local_rpc(&address, vec![newer]).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("merge_tasks failed: {:?}", other));
+            }
+        }
+        match search_tasks_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(tasks)) if tasks.is_empty() => {}
+            other => {
+                fail!(format!("Merge resurrected a trashed task: {:?}", other));
+            }
+        }
+
+        match restore_task_local_rpc(&address, "trash-a".to_string()).await {
+            Ok(Ok(task)) if task.id == "trash-a" => {}
+            other => {
+                fail!(format!("restore_task failed: {:?}", other));
+            }
+        }
+
+        match delete_task_local_rpc(&address, "trash-a".to_string()).await {
+            Ok(Ok(_)) => {}
+            other => {
+                fail!(format!("Second delete failed: {:?}", other));
+            }
+        }
+        match empty_trash_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(1)) => {}
+            other => {
+                fail!(format!("Expected one task purged, got {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Known ids flip, unknown ones are skipped without failing the batch
+    test_toggle_many: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        let mut done = item("bulk-b", "Already done");
+        done.completed = true;
+        seed_tasks(&address, vec![item("bulk-a", "Pending"), done]).await;
+
+        let ids = vec!["bulk-a".to_string(), "nope".to_string(), "bulk-b".to_string()];
+        let toggled = match toggle_many_local_rpc(&address, ids).await {
+            Ok(Ok(tasks)) => tasks,
+            other => {
+                fail!(format!("toggle_many failed: {:?}", other));
+            }
+        };
+        let states: Vec<(&str, bool)> = toggled.iter().map(|t| (t.id.as_str(), t.completed)).collect();
+        if states != [("bulk-a", true), ("bulk-b", false)] {
+            fail!(format!("Unexpected toggle result: {:?}", states));
+        }
+
+        match toggle_many_local_rpc(&address, vec!["nope".to_string()]).await {
+            Ok(Ok(tasks)) if tasks.is_empty() => {}
+            other => {
+                fail!(format!("Unknown ids should give an empty result, got {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Completing a recurring task spawns one successor, however often the
+    // original is reopened and completed again
+    test_recurring_toggle_spawns_once: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        let mut weekly = item("weekly", "Water plants");
+        weekly.recurrence = Some(7 * 86_400);
+        seed_tasks(&address, vec![weekly]).await;
+
+        for expected in [true, false, true] {
+            match toggle_many_local_rpc(&address, vec!["weekly".to_string()]).await {
+                Ok(Ok(tasks)) if tasks.len() == 1 && tasks[0].completed == expected => {}
+                other => {
+                    fail!(format!("Toggle to {} failed: {:?}", expected, other));
+                }
+            }
+        }
+
+        match search_tasks_local_rpc(&address, "water".to_string()).await {
+            Ok(Ok(tasks)) => {
+                let original = tasks.iter().find(|t| t.id == "weekly");
+                let successors: Vec<&TodoItem> = tasks.iter().filter(|t| t.id != "weekly").collect();
+                if tasks.len() != 2 || original.is_none_or(|t| t.recurrence.is_some()) {
+                    fail!(format!("Expected the original plus one successor, got {:?}", tasks));
+                }
+                if successors[0].completed || successors[0].recurrence != Some(7 * 86_400) {
+                    fail!(format!("Successor should be pending and recurring: {:?}", successors[0]));
+                }
+            }
+            other => {
+                fail!(format!("search_tasks failed: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // A parent completed by its last subtask recurs like one completed
+    // directly
+    test_auto_completed_parent_recurs: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        let mut parent = item("chores", "Weekly chores");
+        parent.recurrence = Some(7 * 86_400);
+        let mut child = item("chores-sweep", "Sweep");
+        child.parent_id = Some("chores".to_string());
+        seed_tasks(&address, vec![parent, child]).await;
+
+        match toggle_many_local_rpc(&address, vec!["chores-sweep".to_string()]).await {
+            Ok(Ok(tasks)) if tasks.len() == 1 && tasks[0].completed => {}
+            other => {
+                fail!(format!("Completing the subtask failed: {:?}", other));
+            }
+        }
+
+        match search_tasks_local_rpc(&address, "weekly chores".to_string()).await {
+            Ok(Ok(tasks)) => {
+                let done = tasks.iter().any(|t| t.id == "chores" && t.completed && t.recurrence.is_none());
+                let next = tasks.iter().any(|t| t.id != "chores" && !t.completed && t.recurrence.is_some());
+                if tasks.len() != 2 || !done || !next {
+                    fail!(format!("Expected a completed parent and its next occurrence, got {:?}", tasks));
+                }
+            }
+            other => {
+                fail!(format!("search_tasks failed: {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // With reject_duplicates on, text matching a pending task is a Conflict
+    // regardless of case and padding; done tasks don't count
+    test_reject_duplicates: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        let mut done = item("dup-done", "Water plants");
+        done.completed = true;
+        seed_tasks(&address, vec![item("dup-a", "Buy milk"), done]).await;
+        match set_reject_duplicates_local_rpc(&address, true).await {
+            Ok(Ok(())) => {}
+            other => {
+                fail!(format!("set_reject_duplicates failed: {:?}", other));
+            }
+        }
+
+        let args = ("dup-a".to_string(), "  buy MILK ".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Err(e)) if e.code == TodoErrorCode::Conflict => {}
+            other => {
+                fail!(format!("Duplicate should be a Conflict, got {:?}", other));
+            }
+        }
+        let args = ("dup-a".to_string(), "Water plants".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Ok(_)) => {}
+            other => {
+                fail!(format!("Text of a done task should be allowed, got {:?}", other));
+            }
+        }
+
+        let _ = set_reject_duplicates_local_rpc(&address, false).await;
+        let args = ("dup-a".to_string(), "Buy milk".to_string());
+        match add_subtask_local_rpc(&address, args).await {
+            Ok(Ok(_)) => {}
+            other => {
+                fail!(format!("Duplicates should be allowed by default, got {:?}", other));
+            }
+        }
+
+        Ok(())
+    },
+
+    // Counts are broken down by priority and tag; an empty list is 0% done
+    test_stats: async {
+        let address: Address = ("todo.os", "todo", "todo", "template.os").into();
+
+        reset_tasks(&address).await;
+        let stats = match stats_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => serde_json::from_str::<serde_json::Value>(&json).unwrap_or_default(),
+            other => {
+                fail!(format!("stats failed: {:?}", other));
+            }
+        };
+        if stats["total"] != 0 || stats["completion_pct"] != 0.0 {
+            fail!(format!("Unexpected stats for an empty list: {}", stats));
+        }
+
+        let mut done = item("stats-b", "Ship it");
+        done.completed = true;
+        done.tags = vec!["Work".to_string()];
+        let mut pending = item("stats-a", "Review");
+        pending.tags = vec!["work".to_string(), "home".to_string()];
+        seed_tasks(&address, vec![pending, done]).await;
+        let stats = match stats_local_rpc(&address, "".to_string()).await {
+            Ok(Ok(json)) => serde_json::from_str::<serde_json::Value>(&json).unwrap_or_default(),
+            other => {
+                fail!(format!("stats failed: {:?}", other));
+            }
+        };
+        if stats["total"] != 2
+            || stats["completed"] != 1
+            || stats["incomplete"] != 1
+            || stats["completion_pct"] != 50.0
+            || stats["by_tag"]["work"] != 2
+            || stats["by_tag"]["home"] != 1
+        {
+            fail!(format!("Unexpected stats: {}", stats));
+        }
+
+        Ok(())
+    },
+
 );
\ No newline at end of file
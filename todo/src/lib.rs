@@ -12,16 +12,34 @@ use hyperprocess_macro::*;
 
 use hyperware_process_lib::http::server::{send_ws_push, WsMessageType};
 use hyperware_app_common::{get_server, source, SaveOptions};
-use hyperware_process_lib::{LazyLoadBlob, Address, homepage::add_to_homepage, our};
+use hyperware_process_lib::{LazyLoadBlob, Address, Request, homepage::add_to_homepage, our};
 // you can use these imports when using P2P features from the hyperware_process_lib:
 // Address,                // For P2P addressing
 // ProcessId,              // Process identifiers
 // Request,                // For making requests to other processes/nodes
-use hyperware_process_lib::logging::{error, debug};
+use hyperware_process_lib::logging::{error, info, debug};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use uuid::Uuid; 
 
+/// `debug!` gated on the runtime verbosity chosen with `set_log_level`
+macro_rules! verbose {
+    ($state:expr, $($arg:tt)*) => {
+        if $state.log_level >= LogLevel::Debug {
+            debug!($($arg)*);
+        }
+    };
+}
+
+/// `info!` for state changes worth seeing at the `Info` level and above
+macro_rules! notice {
+    ($state:expr, $($arg:tt)*) => {
+        if $state.log_level >= LogLevel::Info {
+            info!($($arg)*);
+        }
+    };
+}
+
 // =============================================================================
 // CORE TODO APPLICATION DATA STRUCTURES
 // =============================================================================
@@ -32,6 +50,96 @@ pub struct TodoItem {
     id: String,
     text: String,
     completed: bool,
+    /// Unix seconds when the task was last marked done; cleared when it is
+    /// reopened. Tasks completed before this field existed have none.
+    #[serde(default)]
+    completed_at: Option<u64>,
+    /// Optional emoji or short label shown beside the task
+    #[serde(default)]
+    icon: Option<String>,
+    /// Unix seconds of the last change; decides merge conflicts (last write
+    /// wins). Items saved before this field load as 0 and lose every conflict.
+    #[serde(default)]
+    updated_at: u64,
+    /// 0 = low, 1 = medium, 2 = high. Kept numeric because WIT has no
+    /// data-carrying enums; items saved before this field load as medium.
+    #[serde(default = "default_priority")]
+    priority: u8,
+    /// Optional deadline as unix seconds
+    #[serde(default)]
+    due_date: Option<u64>,
+    /// Hidden from active views until this unix time ("not before")
+    #[serde(default)]
+    start_date: Option<u64>,
+    /// Lowercase labels, unique per task
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Whether peers may see this task. Private tasks never leave the node.
+    /// Items saved before this field load as shared, as everything was then.
+    #[serde(default = "default_shared")]
+    shared: bool,
+    /// Expected effort in minutes, used for planning
+    #[serde(default)]
+    estimate_mins: Option<u32>,
+    /// Id of the task this is a subtask of. Kept flat rather than nesting
+    /// `Vec<TodoItem>` because WIT types can't be recursive.
+    #[serde(default)]
+    parent_id: Option<String>,
+    /// Repeat interval in seconds. Completing the task adds a fresh pending
+    /// copy with its dates moved forward by this much.
+    #[serde(default)]
+    recurrence: Option<u64>,
+}
+
+/// Field description served by `get_task_schema` so the frontend can build
+/// edit forms. Hand-maintained: update it whenever `TodoItem` changes.
+fn todo_item_schema() -> serde_json::Value {
+    serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
+        "fields": [
+            { "name": "id", "type": "string", "optional": false, "read_only": true },
+            { "name": "text", "type": "string", "optional": false,
+              "min_length": 1, "max_length": MAX_TASK_LEN },
+            { "name": "completed", "type": "bool", "optional": false },
+            { "name": "completed_at", "type": "timestamp", "optional": true, "read_only": true },
+            { "name": "icon", "type": "string", "optional": true, "max_length": MAX_ICON_LEN },
+            { "name": "updated_at", "type": "timestamp", "optional": false, "read_only": true },
+            { "name": "priority", "type": "u8", "optional": false,
+              "min": 0, "max": MAX_PRIORITY, "default": DEFAULT_PRIORITY },
+            { "name": "due_date", "type": "timestamp", "optional": true },
+            { "name": "start_date", "type": "timestamp", "optional": true },
+            { "name": "tags", "type": "list<string>", "optional": false,
+              "max_length": MAX_TAG_LEN, "lowercase": true, "unique": true },
+            { "name": "shared", "type": "bool", "optional": false },
+            { "name": "estimate_mins", "type": "u32", "optional": true },
+            { "name": "parent_id", "type": "string", "optional": true, "read_only": true },
+            { "name": "recurrence", "type": "seconds", "optional": true, "min": 1 },
+        ],
+    })
+}
+
+/// Priority given to tasks that don't specify one
+const DEFAULT_PRIORITY: u8 = 1;
+/// Highest valid priority value
+const MAX_PRIORITY: u8 = 2;
+
+fn default_priority() -> u8 {
+    DEFAULT_PRIORITY
+}
+
+fn default_shared() -> bool {
+    true
+}
+
+/// Reject priorities outside 0..=MAX_PRIORITY
+fn validate_priority(priority: u8) -> Result<u8, String> {
+    if priority > MAX_PRIORITY {
+        return Err(format!(
+            "Priority must be between 0 and {}, got {}",
+            MAX_PRIORITY, priority
+        ));
+    }
+    Ok(priority)
 }
 
 /// Legacy response structure (kept for compatibility)
@@ -50,10 +158,433 @@ pub struct Item {
     pub id: String,
 }
 
-fn ws_get_tasks(channel_id: u32, tasks: Vec<TodoItem>) {
+/// Complete backup produced by `export_full`: tasks plus every persisted
+/// setting. Settings missing from older backups fall back to defaults.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct FullExport {
+    schema_version: u32,
+    tasks: Vec<TodoItem>,
+    #[serde(default)]
+    log_level: LogLevel,
+    #[serde(default)]
+    text_format: TextFormat,
+    #[serde(default)]
+    collapse_whitespace: bool,
+    #[serde(default)]
+    mute_inbox_zero: bool,
+    #[serde(default)]
+    strict_remote_validation: bool,
+    #[serde(default)]
+    private_by_default: bool,
+    #[serde(default)]
+    reject_duplicates: bool,
+    #[serde(default)]
+    daily_capacity_mins: u32,
+    #[serde(default)]
+    list_title: String,
+    #[serde(default)]
+    list_description: String,
+    #[serde(default)]
+    trash: Vec<TodoItem>,
+}
+
+/// Task-only document produced by `export_tasks` for file download.
+/// `version` is the SCHEMA_VERSION of the items; `exported_at` is unix seconds.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TasksExport {
+    version: u32,
+    exported_at: u64,
+    #[serde(default)]
+    list_title: String,
+    tasks: Vec<TodoItem>,
+}
+
+/// Filter accepted by `query`; every criterion present must match (AND).
+/// Unknown keys are rejected so typos don't silently widen the result.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskFilter {
+    #[serde(default)]
+    completed: Option<bool>,
+    /// Case-insensitive substring of the task text
+    #[serde(default)]
+    text_contains: Option<String>,
+    /// Only tasks at or above this priority
+    #[serde(default)]
+    priority_min: Option<u8>,
+    /// Only tasks due strictly before this unix time
+    #[serde(default)]
+    due_before: Option<u64>,
+    /// Only tasks carrying this tag (case-insensitive)
+    #[serde(default)]
+    tag: Option<String>,
+}
+
+impl TaskFilter {
+    fn matches(&self, task: &TodoItem) -> bool {
+        if let Some(completed) = self.completed {
+            if task.completed != completed {
+                return false;
+            }
+        }
+        if let Some(min) = self.priority_min {
+            if task.priority < min {
+                return false;
+            }
+        }
+        if let Some(before) = self.due_before {
+            if !task.due_date.is_some_and(|due| due < before) {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            let tag = tag.trim().to_lowercase();
+            if !task.tags.iter().any(|t| t.to_lowercase() == tag) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.text_contains {
+            if !task.text.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// WIT world this process is built against; must match `wit_world` below
+const WIT_WORLD: &str = "todo-template-dot-os-v0";
+/// Version of the persisted state layout. Bump it and add a step to
+/// `TodoState::migrate` whenever saved state needs upgrading.
+/// 0: unversioned state from before schema tracking
+/// 1: TodoItem carries priority and due_date
+const SCHEMA_VERSION: u32 = 1;
+
+/// Channels must send something at least this often (seconds) or they
+/// are reaped; the UI sends a `ping` action to stay alive when idle
+const PRESENCE_TIMEOUT_SECS: u64 = 90;
+/// Consecutive failed pushes after which a subscribed peer is dropped
+const MAX_CLIENT_FAILURES: u32 = 5;
+/// Seconds to wait on each subscribed peer when pushing changes
+const NOTIFY_TIMEOUT_SECS: u64 = 5;
+
+/// Most labeled snapshots kept; the oldest is evicted first
+const MAX_SNAPSHOTS: usize = 10;
+/// Upper bound on tasks held across all snapshots combined
+const MAX_SNAPSHOT_TASKS: usize = 5000;
+/// Longest accepted snapshot label, in characters
+const MAX_SNAPSHOT_LABEL_LEN: usize = 64;
+/// Deleted tasks kept for `restore_task`; the oldest are purged beyond this
+const MAX_TRASH: usize = 200;
+
+/// Longest accepted task text, in characters (not bytes)
+const MAX_TASK_LEN: usize = 1000;
+/// Longest accepted tag, in characters
+const MAX_TAG_LEN: usize = 32;
+/// Longest accepted task icon, in characters (room for multi-codepoint emoji)
+const MAX_ICON_LEN: usize = 8;
+/// Longest accepted list title, in characters
+const MAX_LIST_TITLE_LEN: usize = 100;
+/// Longest accepted list description, in characters
+const MAX_LIST_DESCRIPTION_LEN: usize = 500;
+/// Homepage label used while the list has no title
+const DEFAULT_LIST_TITLE: &str = "Todo App";
+
+/// Tasks per `tasks_chunk` frame when the client doesn't ask for a size
+const DEFAULT_STREAM_CHUNK: usize = 100;
+/// Largest chunk size a client may request
+const MAX_STREAM_CHUNK: usize = 1000;
+
+/// Help text returned by `command` for unknown or malformed input
+const COMMAND_USAGE: &str =
+    "Usage: add <text> | done <number> | rm <number> | list";
+
+/// Kind of failure a `TodoError` reports, so clients can branch on it
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TodoErrorCode {
+    /// No task (or snapshot) with the requested id
+    NotFound,
+    /// Task text was empty after cleaning and trimming
+    EmptyText,
+    /// An id or task number that can't refer to a task
+    InvalidId,
+    /// The request clashes with existing state, e.g. a backup from a newer version
+    Conflict,
+    /// Any other rejected input: lengths, ranges, malformed JSON
+    InvalidInput,
+}
+
+/// Handler error, serialized as `{ "code": ..., "message": ... }`. A struct
+/// rather than a data-carrying enum so it stays WIT-compatible; `message`
+/// is the same human-readable text handlers returned before.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TodoError {
+    code: TodoErrorCode,
+    message: String,
+}
+
+impl TodoError {
+    fn new(code: TodoErrorCode, message: impl Into<String>) -> Self {
+        TodoError { code, message: message.into() }
+    }
+
+    fn not_found(id: &str) -> Self {
+        Self::new(TodoErrorCode::NotFound, format!("Task with id '{}' not found", id))
+    }
+}
+
+impl std::fmt::Display for TodoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<TodoError> for String {
+    fn from(e: TodoError) -> String {
+        e.message
+    }
+}
+
+/// Plain validation messages from the free helpers are `InvalidInput`
+impl From<String> for TodoError {
+    fn from(message: String) -> Self {
+        Self::new(TodoErrorCode::InvalidInput, message)
+    }
+}
+
+/// Runtime log verbosity; errors are always logged
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    /// Adds state changes: migrations, purges, reaped channels, merges
+    Info,
+    /// Adds per-request tracing
+    #[default]
+    Debug,
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.trim().to_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            other => Err(format!(
+                "Unknown log level '{}', expected one of: error, info, debug",
+                other
+            )),
+        }
+    }
+}
+
+/// How task text is interpreted before it is stored
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum TextFormat {
+    /// Stored exactly as entered
+    #[default]
+    Plain,
+    /// Limited inline markdown; see `sanitize_markdown`
+    Markdown,
+}
+
+impl std::str::FromStr for TextFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "plain" => Ok(TextFormat::Plain),
+            "markdown" => Ok(TextFormat::Markdown),
+            other => Err(format!(
+                "Unknown text format '{}', expected plain or markdown",
+                other
+            )),
+        }
+    }
+}
+
+/// Parse `[label](url)` starting at the `[` at `start`.
+/// Returns the label, the url and the index just past the closing `)`.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close = start + chars[start..].iter().position(|&c| c == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    // Match the closing paren, allowing balanced parens inside the url
+    let mut depth = 0usize;
+    let mut end = None;
+    for (j, &c) in chars.iter().enumerate().skip(close + 1) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(j);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let end = end?;
+    let label = chars[start + 1..close].iter().collect();
+    let url = chars[close + 2..end].iter().collect();
+    Some((label, url, end + 1))
+}
+
+/// Reduce `text` to the inline markdown the UI renders: **bold**, *italic*,
+/// _italic_ and [links](https://...). Other constructs are stripped down to
+/// their text: HTML tags, images, inline code, strikethrough, headings and
+/// block quotes. Links to anything but http(s) keep only their label.
+fn sanitize_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (n, line) in text.split('\n').enumerate() {
+        if n > 0 {
+            out.push('\n');
+        }
+        let trimmed = line.trim_start();
+        let line = if trimmed.starts_with('#') || trimmed.starts_with('>') {
+            trimmed.trim_start_matches(|c: char| c == '#' || c == '>' || c.is_whitespace())
+        } else {
+            line
+        };
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '<' => match chars[i..].iter().position(|&c| c == '>') {
+                    Some(len) => i += len + 1,
+                    None => {
+                        out.push('<');
+                        i += 1;
+                    }
+                },
+                '`' => i += 1,
+                '~' if chars.get(i + 1) == Some(&'~') => i += 2,
+                '!' if chars.get(i + 1) == Some(&'[') => match parse_link(&chars, i + 1) {
+                    Some((alt, _, end)) => {
+                        out.push_str(&sanitize_markdown(&alt));
+                        i = end;
+                    }
+                    None => {
+                        out.push('!');
+                        i += 1;
+                    }
+                },
+                '[' => match parse_link(&chars, i) {
+                    Some((label, url, end)) => {
+                        let label = sanitize_markdown(&label);
+                        let lower = url.trim().to_lowercase();
+                        if lower.starts_with("https://") || lower.starts_with("http://") {
+                            out.push_str(&format!("[{}]({})", label, url.trim()));
+                        } else {
+                            out.push_str(&label);
+                        }
+                        i = end;
+                    }
+                    None => {
+                        out.push('[');
+                        i += 1;
+                    }
+                },
+                c => {
+                    out.push(c);
+                    i += 1;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Clean and trim task text for a list with the given settings, rejecting
+/// it if nothing is left. Every path that saves task text goes through here.
+fn prepare_text_for(text: &str, format: TextFormat, collapse_whitespace: bool) -> Result<String, TodoError> {
+    let text = match format {
+        TextFormat::Plain => text.to_string(),
+        TextFormat::Markdown => sanitize_markdown(text),
+    };
+    let text = if collapse_whitespace {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        text.trim().to_string()
+    };
+    if text.is_empty() {
+        return Err(TodoError::new(TodoErrorCode::EmptyText, "Task text cannot be empty"));
+    }
+    validate_text_len(&text)?;
+    Ok(text)
+}
+
+/// One `<li>` of the printable checklist produced by `export_print`
+fn print_item(task: &TodoItem) -> String {
+    let icon = task
+        .icon
+        .as_deref()
+        .map(|i| format!("{} ", escape_html(i)))
+        .unwrap_or_default();
+    let due = task
+        .due_date
+        .map(|d| format!(" <small>(due {})</small>", format_date(d)))
+        .unwrap_or_default();
+    format!(
+        "<li{}><input type=\"checkbox\" disabled{}> {}{}{}</li>\n",
+        if task.completed { " class=\"done\"" } else { "" },
+        if task.completed { " checked" } else { "" },
+        icon,
+        escape_html(&task.text),
+        due,
+    )
+}
+
+/// Format unix seconds as a UTC calendar date (YYYY-MM-DD)
+fn format_date(secs: u64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Escape text for safe inclusion in HTML element content or attributes
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Push a `tasks_overview` page. With no `limit` the whole list from `offset`
+/// on is sent; `total` always reports the full list length.
+fn ws_get_tasks(channel_id: u32, tasks: Vec<TodoItem>, offset: usize, limit: Option<usize>) {
+    let total = tasks.len();
+    let page: Vec<TodoItem> = tasks
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
     let response = serde_json::json!({
         "type": "tasks_overview",
-        "tasks": tasks
+        "tasks": page,
+        "total": total,
+        "offset": offset,
+        "limit": limit
     });
 
     let response_bytes = response.to_string().into_bytes();
@@ -65,12 +596,33 @@ fn ws_get_tasks(channel_id: u32, tasks: Vec<TodoItem>) {
     send_ws_push(channel_id, WsMessageType::Text, response_blob);
 }
 
-fn ws_add_task(channel_id: u32, task: TodoItem, tasks: Vec<TodoItem>) {
+/// Tell the client its last message failed, with the same `code` HTTP
+/// handlers return so the UI can branch on it
+fn ws_error(channel_id: u32, code: TodoErrorCode, message: &str) {
+    let response = serde_json::json!({
+        "type": "error",
+        "code": code,
+        "message": message
+    });
+
+    let response_bytes = response.to_string().into_bytes();
+
+    let response_blob = LazyLoadBlob {
+        mime: Some("application/json".to_string()),
+        bytes: response_bytes,
+    };
+    send_ws_push(channel_id, WsMessageType::Text, response_blob);
+}
 
+/// Reply to `get_task` with the task, or an error frame if it wasn't found
+fn ws_get_task(channel_id: u32, result: Result<TodoItem, TodoError>) {
+    let task = match result {
+        Ok(task) => task,
+        Err(e) => return ws_error(channel_id, e.code, &e.message),
+    };
     let response = serde_json::json!({
-        "type": "task_added",
-        "task": task,
-        "tasks": tasks
+        "type": "task",
+        "task": task
     });
 
     let response_bytes = response.to_string().into_bytes();
@@ -82,12 +634,53 @@ fn ws_add_task(channel_id: u32, task: TodoItem, tasks: Vec<TodoItem>) {
     send_ws_push(channel_id, WsMessageType::Text, response_blob);
 }
 
-fn ws_toggle_task(channel_id: u32, task: TodoItem, tasks: Vec<TodoItem>) {
+/// Send the list as a sequence of `tasks_chunk` frames so large lists can be
+/// rendered progressively; an empty list still produces one (last) frame.
+fn ws_stream_tasks(channel_id: u32, tasks: &[TodoItem], chunk_size: usize) {
+    let chunks: Vec<&[TodoItem]> = if tasks.is_empty() {
+        vec![&[]]
+    } else {
+        tasks.chunks(chunk_size).collect()
+    };
+    let count = chunks.len();
+
+    for (seq, chunk) in chunks.into_iter().enumerate() {
+        let response = serde_json::json!({
+            "type": "tasks_chunk",
+            "seq": seq,
+            "last": seq + 1 == count,
+            "tasks": chunk
+        });
+
+        let response_bytes = response.to_string().into_bytes();
+
+        let response_blob = LazyLoadBlob {
+            mime: Some("application/json".to_string()),
+            bytes: response_bytes,
+        };
+        send_ws_push(channel_id, WsMessageType::Text, response_blob);
+    }
+}
+
+fn ws_inbox_zero(channel_id: u32) {
+    let response = serde_json::json!({
+        "type": "inbox_zero"
+    });
+
+    let response_bytes = response.to_string().into_bytes();
+
+    let response_blob = LazyLoadBlob {
+        mime: Some("application/json".to_string()),
+        bytes: response_bytes,
+    };
+    send_ws_push(channel_id, WsMessageType::Text, response_blob);
+}
 
+fn ws_presence(channel_id: u32, channels: usize, reaped: &[u32]) {
     let response = serde_json::json!({
-        "type": "task_toggled",
-        "task": task,
-        "tasks": tasks
+        "type": "presence",
+        "channels": channels,
+        "reaped": reaped
     });
 
     let response_bytes = response.to_string().into_bytes();
@@ -128,165 +721,2208 @@ pub struct TodoState {
     /// Active WebSocket channel IDs (not serialized)
     #[serde(skip)]
     ws_channels: HashSet<u32>,
-    // add clients
+    /// Last heartbeat (ping/pong) per channel, in unix seconds (not serialized)
+    #[serde(skip)]
+    last_seen: HashMap<u32, u64>,
+    /// Filter each channel set with `subscribe_filter`; broadcasts only
+    /// push matching tasks to it (not serialized, channel ids don't
+    /// survive a restart)
+    #[serde(skip)]
+    channel_filters: HashMap<u32, TaskFilter>,
+    /// Peers subscribed to task updates via `subscribe`, without duplicates
     clients: Vec<Address>,
+    /// Consecutive failed pushes per subscribed peer (not serialized)
+    #[serde(skip)]
+    client_failures: HashMap<String, u32>,
+    /// Pending reminders as (task id, unix seconds), at most one per task;
+    /// re-armed on init. A Vec rather than a HashMap to stay WIT-compatible.
+    #[serde(default)]
+    reminders: Vec<(String, u64)>,
+    /// Deleted tasks, oldest first, until restored or purged
+    #[serde(default)]
+    trash: Vec<TodoItem>,
+    /// Monotonic counter feeding the fallback id generator
+    #[serde(default)]
+    id_counter: u64,
+    /// Layout version of this saved state; see SCHEMA_VERSION
+    #[serde(default)]
+    schema_version: u32,
+    /// Verbosity applied to handler debug logs
+    #[serde(default)]
+    log_level: LogLevel,
+    /// Labeled copies of `tasks` as (label, created_at, tasks), oldest first
+    #[serde(default)]
+    snapshots: Vec<(String, u64, Vec<TodoItem>)>,
+    /// Whether task text is stored as plain text or sanitized markdown
+    #[serde(default)]
+    text_format: TextFormat,
+    /// Collapse runs of whitespace in task text to single spaces on save
+    #[serde(default)]
+    collapse_whitespace: bool,
+    /// Reject a whole peer batch on any invalid item instead of skipping it
+    #[serde(default)]
+    strict_remote_validation: bool,
+    /// Suppresses the `inbox_zero` push (on by default)
+    #[serde(default)]
+    mute_inbox_zero: bool,
+    /// New tasks start private instead of shared
+    #[serde(default)]
+    private_by_default: bool,
+    /// Refuse new tasks whose text matches a pending task, ignoring case
+    #[serde(default)]
+    reject_duplicates: bool,
+    /// Minutes of task work planned per day; 0 means not set
+    #[serde(default)]
+    daily_capacity_mins: u32,
+    /// Name shown on the homepage and in exports; empty means unnamed
+    #[serde(default)]
+    list_title: String,
+    /// Free-form note about the list, shown in the homepage widget
+    #[serde(default)]
+    list_description: String,
+    /// Fixed time returned by `now()`; only settable in simulation mode
+    #[serde(skip)]
+    clock_override: Option<u64>,
 }
 
-// =============================================================================
-// HYPERPROCESS CONFIGURATION
-// =============================================================================
+/// Reject task text longer than MAX_TASK_LEN characters
+fn validate_text_len(text: &str) -> Result<(), String> {
+    if text.chars().count() > MAX_TASK_LEN {
+        return Err(format!("Task text exceeds {} characters", MAX_TASK_LEN));
+    }
+    Ok(())
+}
 
-#[hyperprocess(
-    name = "todo",
-    ui = Some(HttpBindingConfig::default()),
-    // HTTP API endpoints - MUST include /api for frontend communication
-    // Any endpoint referenced in HTTP handlers must be first bound here
-    // No dynamic paths allowed, only static ones
-    endpoints = vec![
-        Binding::Http {
-            path: "/health",
-            config: HttpBindingConfig::new(false, false, false, None),
-        },
-        Binding::Ws {
-            path: "/ws",
-            config: WsBindingConfig::new(false, false, false),
-        },
-        Binding::Http {
-            path: "/api",
-            config: HttpBindingConfig::new(false, false, false, None),
-        },
-    ],
-    // State persistence options:
-    // - EveryMessage: Save after each message (safest, slower)
-    // - OnInterval(n): Save every n seconds
-    // - Never: No automatic saves (manual only)
-    save_config = SaveOptions::EveryMessage,
-    wit_world = "todo-template-dot-os-v0"
-)]
+/// Trim list metadata and reject it if longer than `max` characters
+fn validate_list_field(value: &str, name: &str, max: usize) -> Result<String, String> {
+    let value = value.trim();
+    if value.chars().count() > max {
+        return Err(format!("List {} exceeds {} characters", name, max));
+    }
+    Ok(value.to_string())
+}
 
-// =============================================================================
-// CORE TODO APPLICATION IMPLEMENTATION
-// =============================================================================
+/// Trim and lowercase a tag so matching and dedup are case-insensitive
+fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tag exceeds {} characters", MAX_TAG_LEN));
+    }
+    Ok(tag)
+}
+
+/// Basic sanity checks for a task received from a peer
+fn validate_item(task: &TodoItem) -> Result<(), String> {
+    if task.id.trim().is_empty() {
+        return Err("Task id cannot be empty".to_string());
+    }
+    if task.text.trim().is_empty() {
+        return Err("Task text cannot be empty".to_string());
+    }
+    validate_text_len(&task.text)?;
+    validate_priority(task.priority)?;
+    for tag in &task.tags {
+        normalize_tag(tag)?;
+    }
+    if let Some(icon) = &task.icon {
+        if icon.chars().count() > MAX_ICON_LEN {
+            return Err(format!("Icon exceeds {} characters", MAX_ICON_LEN));
+        }
+    }
+    if task.recurrence == Some(0) {
+        return Err("Recurrence interval must be at least one second".to_string());
+    }
+    Ok(())
+}
 
+/// Current unix time in seconds from the host clock (0 if unavailable).
+/// Handlers should go through `TodoState::now` so tests can pin the time.
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Helpers shared by the handlers below; kept outside the #[hyperprocess] impl
+// so the macro doesn't try to expose them as endpoints.
 impl TodoState {
-    /// Initialize the application state
-    #[init]
-    async fn initialize(&mut self) {
-        debug!("Initializing todo list state");
-        // Add your app to the Hyperware homepage
-        // Parameters: name, icon (emoji), path, widget
-        add_to_homepage("Todo App", Some("👀"), Some("/"), None);
+    /// Upgrade state saved by an older version one step at a time until it
+    /// matches SCHEMA_VERSION. Fresh state starts at 0 and passes through
+    /// every step as a no-op.
+    fn migrate(&mut self) {
+        while self.schema_version < SCHEMA_VERSION {
+            match self.schema_version {
+                0 => {
+                    // v0 items predate priority and due_date; serde filled in
+                    // the defaults, so only clamp anything out of range
+                    for task in &mut self.tasks {
+                        if validate_priority(task.priority).is_err() {
+                            task.priority = DEFAULT_PRIORITY;
+                        }
+                    }
+                }
+                version => {
+                    error!("No migration from schema version {}", version);
+                    break;
+                }
+            }
+            self.schema_version += 1;
+            notice!(self, "Migrated state to schema version {}", self.schema_version);
+        }
+    }
 
-        // Initialize your app state
-        self.tasks = Vec::new();
-        self.ws_channels = HashSet::new();
-        self.clients = Vec::new();
-        // You can use our() to get the address of the current process
-        let our = our();
-        debug!("Process has just started on here: {}", our);
+    /// (Re)register the homepage tile, named after the list title and with a
+    /// widget showing the description when there is one
+    fn register_homepage(&self) {
+        let title = if self.list_title.is_empty() {
+            DEFAULT_LIST_TITLE
+        } else {
+            &self.list_title
+        };
+        let widget = (!self.list_description.is_empty()).then(|| {
+            format!(
+                "<html><body style=\"font-family: sans-serif\"><strong>{}</strong><p>{}</p></body></html>",
+                escape_html(title),
+                escape_html(&self.list_description)
+            )
+        });
+        add_to_homepage(title, Some("👀"), Some("/"), widget.as_deref());
     }
 
-    #[local]
-    #[remote]
-    async fn share_tasks(&mut self, request: String) -> Vec<TodoItem> {
-        let source = source();
-        debug!("Sharing tasks with {}", source);
-        let _value = request;
-        self.tasks.clone()
+    /// Current unix time in seconds, honoring a test clock override
+    fn now(&self) -> u64 {
+        self.clock_override.unwrap_or_else(now_secs)
     }
 
-    #[local]
-    #[remote]
-    async fn merge_tasks(&mut self, tasks: Vec<TodoItem>) -> Result<(), String> {
-        let source = source();
-        debug!("Merging tasks with {}", source);
-        self.tasks.extend(tasks);
-        Ok(())
+    /// `prepare_text_for` under the current list settings
+    fn prepare_text(&self, text: &str) -> Result<String, TodoError> {
+        prepare_text_for(text, self.text_format, self.collapse_whitespace)
     }
 
-    // HTTP ENDPOINT WITH PARAMETERS
-    // Parameters are sent as either:
-    // - Single value: { "MethodName": value }
-    // - Multiple values as tuple: { "MethodName": [val1, val2] }
-    #[http]
-    async fn get_tasks(&self, request: String) -> Result<Vec<TodoItem>, String> {
-        debug!("Request: {:?}", request);
-        debug!("Fetching tasks");
-        Ok(self.tasks.clone())
+    /// Clean an incoming task's text like locally entered text, then run
+    /// `validate_item` on the result
+    fn prepare_item(&self, mut task: TodoItem) -> Result<TodoItem, String> {
+        task.text = self.prepare_text(&task.text)?;
+        validate_item(&task)?;
+        Ok(task)
     }
 
-    // WEBSOCKET ENDPOINT
-    // WebSocket messages are sent as JSON blobs
-    // The message type is specified in the WsMessageType enum
-    // The blob contains the message data
-    #[ws]
-    fn websocket(&mut self, channel_id: u32, message_type: WsMessageType, blob: LazyLoadBlob) {
-        match message_type {
-            WsMessageType::Text => {
-                // Get the message from the blob
-                if let Ok(message) = String::from_utf8(blob.bytes.clone()) {
-                    debug!("Received WebSocket text message: {}", message);
-                    // Parse the message as JSON
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&message) {
-                        // Handle different message types
-                        if let Some(action) = json.get("action").and_then(|v| v.as_str()) {
-                            match action {
-                                "get_tasks" => {
-                                    debug!("Getting tasks on channel {}", channel_id);
-                                    ws_get_tasks(channel_id, self.tasks.clone());
-                                }
-                                "add_task" => {
-                                    if let Some(text) = json.get("text").and_then(|v| v.as_str()) {
-                                        if !text.trim().is_empty() {
-                                            debug!("Adding task on channel {}", channel_id);
-                                            let new_task = TodoItem {
-                                                id: Uuid::new_v4().to_string(),
-                                                text: text.to_string(),
-                                                completed: false,
-                                            };
-                                            self.tasks.push(new_task.clone());
-                                            ws_add_task(channel_id, new_task.clone(), self.tasks.clone());
-                                        } else {
-                                            error!("Task text cannot be empty");
-                                        }
-                                    }
-                                }
-                                "toggle_task" => {
-                                    if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
-                                        if let Some(task) =
-                                            self.tasks.iter_mut().find(|t| t.id == id)
-                                        {
-                                            task.completed = !task.completed;
-                                            ws_toggle_task(channel_id, task.clone(), self.tasks.clone());
-                                        } else {
-                                            error!("Task with id '{}' not found", id);
+    /// Mark a task done or pending, stamping `completed_at` accordingly
+    /// Completing the last pending subtask also completes its parent, and
+    /// completing a recurring task schedules its next occurrence. Returns
+    /// the task plus any ancestors completed along with it, so callers can
+    /// pass them all on to peers.
+    fn set_completed(&mut self, id: &str, completed: bool) -> Result<(TodoItem, Vec<TodoItem>), TodoError> {
+        let now = self.now();
+        let task = self.touch_task(id)?;
+        let newly_completed = completed && !task.completed;
+        task.completed = completed;
+        task.completed_at = completed.then_some(now);
+        let parent_id = task.parent_id.clone();
+        let mut finished = Vec::new();
+        if newly_completed {
+            finished.push(id.to_string());
+        }
+        if completed {
+            self.cancel_reminder(id);
+            finished.extend(self.complete_finished_parents(parent_id));
+        }
+        // Ancestors get the same treatment as the task itself
+        for done in &finished {
+            self.cancel_reminder(done);
+            self.schedule_next_occurrence(done);
+        }
+        let task = self.find_task(id)?.clone();
+        let ancestors = finished
+            .iter()
+            .filter(|done| *done != id)
+            .filter_map(|done| self.find_task(done).ok().cloned())
+            .collect();
+        Ok((task, ancestors))
+    }
+
+    /// Append the next pending copy of just-completed recurring task `id`.
+    /// Dates move forward by one interval; a task without a due date gets
+    /// one an interval after completion. The recurrence moves to the copy,
+    /// so reopening and completing the original doesn't spawn another.
+    fn schedule_next_occurrence(&mut self, id: &str) {
+        let Ok(done) = self.find_task_mut(id) else { return };
+        let Some(interval) = done.recurrence.take() else { return };
+        let done = done.clone();
+        let now = self.now();
+        let next = TodoItem {
+            id: self.next_id(),
+            completed: false,
+            completed_at: None,
+            updated_at: now,
+            due_date: Some(done.due_date.unwrap_or(now).saturating_add(interval)),
+            start_date: done.start_date.map(|start| start.saturating_add(interval)),
+            recurrence: Some(interval),
+            ..done.clone()
+        };
+        verbose!(self, "Scheduled next occurrence of {} as {}", done.id, next.id);
+        if let Some(due) = next.due_date {
+            self.arm_reminder(&next.id, due);
+        }
+        self.tasks.push(next);
+    }
+
+    /// Walk up from `parent_id`, completing each ancestor whose subtasks are
+    /// now all done, and return their ids. Stops at the first one with
+    /// pending subtasks or that is already complete, which also ends the
+    /// walk on a malformed cycle.
+    fn complete_finished_parents(&mut self, mut parent_id: Option<String>) -> Vec<String> {
+        let now = self.now();
+        let mut completed = Vec::new();
+        while let Some(id) = parent_id {
+            let pending = self
+                .tasks
+                .iter()
+                .any(|t| t.parent_id.as_ref() == Some(&id) && !t.completed);
+            if pending || self.find_task(&id).is_ok_and(|parent| parent.completed) {
+                break;
+            }
+            verbose!(self, "All subtasks of {} are done, completing it", id);
+            let Ok(parent) = self.touch_task(&id) else { break };
+            parent.completed = true;
+            parent.completed_at = Some(now);
+            parent_id = parent.parent_id.clone();
+            completed.push(id);
+        }
+        completed
+    }
+
+    /// Promote subtasks whose parent no longer exists to top-level tasks
+    fn detach_orphans(&mut self) {
+        let ids: HashSet<String> = self.tasks.iter().map(|t| t.id.clone()).collect();
+        for task in &mut self.tasks {
+            if task.parent_id.as_ref().is_some_and(|p| !ids.contains(p)) {
+                task.parent_id = None;
+            }
+        }
+    }
+
+    /// Validate text and append a new pending task, returning it. With
+    /// `reject_duplicates` on, text matching a pending task is a Conflict.
+    fn create_task(
+        &mut self,
+        text: &str,
+        priority: Option<u8>,
+        due_date: Option<u64>,
+    ) -> Result<TodoItem, TodoError> {
+        let text = self.prepare_text(text)?;
+        let priority = validate_priority(priority.unwrap_or(DEFAULT_PRIORITY))?;
+        if self.reject_duplicates {
+            let key = text.to_lowercase();
+            if self
+                .tasks
+                .iter()
+                .any(|t| !t.completed && t.text.trim().to_lowercase() == key)
+            {
+                return Err(TodoError::new(TodoErrorCode::Conflict, "Duplicate task"));
+            }
+        }
+        let new_task = TodoItem {
+            id: self.next_id(),
+            text,
+            completed: false,
+            completed_at: None,
+            icon: None,
+            updated_at: self.now(),
+            priority,
+            due_date,
+            start_date: None,
+            tags: Vec::new(),
+            shared: !self.private_by_default,
+            estimate_mins: None,
+            parent_id: None,
+            recurrence: None,
+        };
+        self.tasks.push(new_task.clone());
+        if let Some(due) = due_date {
+            self.arm_reminder(&new_task.id, due);
+        }
+        Ok(new_task)
+    }
+
+    /// Produce a task id that is unique within this list.
+    /// Prefers a random v4 UUID; if the RNG hands back an id we already hold
+    /// (e.g. a deterministic source on a constrained WASM host) we fall back to
+    /// one built from our node name, the current time and a persisted counter.
+    fn next_id(&mut self) -> String {
+        self.id_counter = self.id_counter.wrapping_add(1);
+        let id = Uuid::new_v4().to_string();
+        if !self.tasks.iter().any(|t| t.id == id) {
+            return id;
+        }
+        error!("Generated duplicate task id {}, using fallback id", id);
+        loop {
+            let fallback = format!("{}-{}-{}", our().node, self.now(), self.id_counter);
+            if !self.tasks.iter().any(|t| t.id == fallback) {
+                return fallback;
+            }
+            self.id_counter = self.id_counter.wrapping_add(1);
+        }
+    }
+
+    /// Look up a task by id
+    fn find_task(&self, id: &str) -> Result<&TodoItem, TodoError> {
+        self.tasks
+            .iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| TodoError::not_found(id))
+    }
+
+    /// Look up a task by id for modification
+    fn find_task_mut(&mut self, id: &str) -> Result<&mut TodoItem, TodoError> {
+        self.tasks
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| TodoError::not_found(id))
+    }
+
+    /// Look up a task about to be modified and stamp its `updated_at`
+    fn touch_task(&mut self, id: &str) -> Result<&mut TodoItem, TodoError> {
+        let now = self.now();
+        let task = self.find_task_mut(id)?;
+        task.updated_at = now;
+        Ok(task)
+    }
+
+    /// Move a task to the trash by id, returning it
+    fn remove_task(&mut self, id: &str) -> Result<TodoItem, TodoError> {
+        let index = self
+            .tasks
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| TodoError::not_found(id))?;
+        let removed = self.tasks.remove(index);
+        self.move_to_trash(vec![removed.clone()]);
+        Ok(removed)
+    }
+
+    /// Move every completed task to the trash and tell open channels,
+    /// returning how many were removed
+    fn remove_completed(&mut self) -> usize {
+        let (completed, pending): (Vec<TodoItem>, Vec<TodoItem>) =
+            std::mem::take(&mut self.tasks).into_iter().partition(|t| t.completed);
+        self.tasks = pending;
+        let removed = completed.len();
+        self.move_to_trash(completed);
+        if removed > 0 {
+            notice!(self, "Cleared {} completed tasks", removed);
+            self.broadcast_tasks("tasks_overview");
+        }
+        removed
+    }
+
+    /// Put already-removed tasks in the trash, cancel their reminders and
+    /// promote their subtasks. The oldest trash beyond MAX_TRASH is purged.
+    fn move_to_trash(&mut self, removed: Vec<TodoItem>) {
+        for task in removed {
+            self.cancel_reminder(&task.id);
+            self.trash.retain(|t| t.id != task.id);
+            self.trash.push(task);
+        }
+        if self.trash.len() > MAX_TRASH {
+            let purged = self.trash.len() - MAX_TRASH;
+            self.trash.drain(..purged);
+            notice!(self, "Purged {} tasks from a full trash", purged);
+        }
+        self.detach_orphans();
+    }
+
+    /// Tasks peers are allowed to see
+    fn shared_tasks(&self) -> Vec<TodoItem> {
+        self.tasks.iter().filter(|t| t.shared).cloned().collect()
+    }
+
+    /// Put tasks in the order of `ordered_ids`. Unknown ids are ignored and
+    /// tasks not mentioned keep their relative order after the listed ones.
+    fn reorder(&mut self, ordered_ids: &[String]) {
+        let mut ordered = Vec::with_capacity(self.tasks.len());
+        for id in ordered_ids {
+            if let Some(i) = self.tasks.iter().position(|t| &t.id == id) {
+                ordered.push(self.tasks.remove(i));
+            }
+        }
+        ordered.append(&mut self.tasks);
+        self.tasks = ordered;
+    }
+
+    /// The list as shown in active views: tasks with a future start date
+    /// are hidden unless `include_future` is set
+    fn active_tasks(&self, include_future: bool) -> Vec<TodoItem> {
+        let now = self.now();
+        self.tasks
+            .iter()
+            .filter(|t| include_future || t.start_date.is_none_or(|start| start <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Tasks paired with their 1-based display number. Numbers follow list
+    /// order and are derived on every read, never stored.
+    fn numbered_tasks(&self) -> impl Iterator<Item = (usize, &TodoItem)> {
+        self.tasks.iter().enumerate().map(|(i, t)| (i + 1, t))
+    }
+
+    /// Resolve a display number from `numbered_tasks` to a task id
+    fn id_for_number(&self, number: &str) -> Result<String, TodoError> {
+        let number: usize = number.trim().parse().map_err(|_| {
+            TodoError::new(
+                TodoErrorCode::InvalidId,
+                format!("'{}' is not a task number. {}", number.trim(), COMMAND_USAGE),
+            )
+        })?;
+        self.numbered_tasks()
+            .find(|(n, _)| *n == number)
+            .map(|(_, t)| t.id.clone())
+            .ok_or_else(|| TodoError::new(TodoErrorCode::NotFound, format!("No task number {}", number)))
+    }
+
+    /// Validate a batch received from `origin`. In strict mode any invalid
+    /// item rejects the whole batch; otherwise invalid items are logged and
+    /// dropped.
+    fn filter_valid(&self, tasks: Vec<TodoItem>, origin: &str) -> Result<Vec<TodoItem>, String> {
+        let mut valid = Vec::with_capacity(tasks.len());
+        for (index, task) in tasks.into_iter().enumerate() {
+            match self.prepare_item(task) {
+                Ok(task) => valid.push(task),
+                Err(e) if self.strict_remote_validation => {
+                    return Err(format!(
+                        "Rejected batch from {}: task at index {} is invalid: {}",
+                        origin, index, e
+                    ));
+                }
+                Err(e) => {
+                    error!("Skipping invalid task at index {} from {}: {}", index, origin, e);
+                }
+            }
+        }
+        Ok(valid)
+    }
+
+    /// Fold tasks received from a peer into the local list, matching by id.
+    /// Unknown ids are appended. For a known id the copy with the later
+    /// `updated_at` wins; on a tie a completed copy beats a pending one.
+    /// Returns (inserted, updated).
+    fn merge_items(&mut self, tasks: Vec<TodoItem>) -> (usize, usize) {
+        let mut positions: HashMap<String, usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.id.clone(), i))
+            .collect();
+        let (mut inserted, mut updated) = (0, 0);
+
+        for incoming in tasks {
+            // Deleted here; a peer's copy must not bring it back
+            if self.trash.iter().any(|t| t.id == incoming.id) {
+                continue;
+            }
+            match positions.get(&incoming.id) {
+                Some(&i) => {
+                    let local = &self.tasks[i];
+                    let newer = incoming.updated_at > local.updated_at;
+                    let tie_break = incoming.updated_at == local.updated_at
+                        && incoming.completed
+                        && !local.completed;
+                    if newer || tie_break {
+                        self.tasks[i] = incoming;
+                        updated += 1;
+                    }
+                }
+                None => {
+                    positions.insert(incoming.id.clone(), self.tasks.len());
+                    self.tasks.push(incoming);
+                    inserted += 1;
+                }
+            }
+        }
+        (inserted, updated)
+    }
+
+    /// Push `inbox_zero` to every channel once no pending tasks remain
+    fn check_inbox_zero(&self) {
+        if self.mute_inbox_zero
+            || self.tasks.is_empty()
+            || self.tasks.iter().any(|t| !t.completed)
+        {
+            return;
+        }
+        for &channel_id in &self.ws_channels {
+            ws_inbox_zero(channel_id);
+        }
+    }
+
+    /// Close channels whose last heartbeat is older than the presence
+    /// window and tell the remaining ones. `current` just sent a message,
+    /// so it is never reaped here.
+    fn reap_idle_channels(&mut self, current: u32) {
+        let now = self.now();
+        let stale: Vec<u32> = self
+            .last_seen
+            .iter()
+            .filter(|(&id, &seen)| {
+                id != current && now.saturating_sub(seen) > PRESENCE_TIMEOUT_SECS
+            })
+            .map(|(&id, _)| id)
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+
+        for id in &stale {
+            notice!(self, "Reaping idle WebSocket channel {}", id);
+            self.last_seen.remove(id);
+            self.ws_channels.remove(id);
+            self.channel_filters.remove(id);
+            send_ws_push(
+                *id,
+                WsMessageType::Close,
+                LazyLoadBlob {
+                    mime: None,
+                    bytes: vec![],
+                },
+            );
+            if let Some(server) = get_server() {
+                server.handle_websocket_close(*id);
+            }
+        }
+        for &channel_id in &self.ws_channels {
+            ws_presence(channel_id, self.ws_channels.len(), &stale);
+        }
+    }
+
+    /// Push a `{"type": msg_type, "tasks": [...]}` frame to every channel
+    fn broadcast_tasks(&mut self, msg_type: &str) {
+        let response = serde_json::json!({
+            "type": msg_type
+        });
+        self.broadcast_with_tasks(response, None);
+    }
+
+    /// Show tasks merged in from a peer to open UIs. Skips building the
+    /// frame when the merge changed nothing or no channel is connected.
+    fn broadcast_merged(&mut self, changed: usize) {
+        if changed == 0 || self.ws_channels.is_empty() {
+            return;
+        }
+        self.broadcast_tasks("tasks_overview");
+    }
+
+    /// Like `broadcast_tasks`, but also names the task that changed
+    fn broadcast_task(&mut self, msg_type: &str, task: &TodoItem) {
+        let response = serde_json::json!({
+            "type": msg_type,
+            "task": task
+        });
+        self.broadcast_with_tasks(response, Some(task));
+    }
+
+    /// `send_ws_push` can't report a dead channel, so before sending we drop
+    /// any channel the HTTP server no longer has open on our /ws binding.
+    fn prune_channels(&mut self) {
+        if let Some(server) = get_server() {
+            let open = server.get_ws_channels().get("/ws").cloned().unwrap_or_default();
+            self.ws_channels.retain(|id| open.contains(id));
+            self.last_seen.retain(|id, _| open.contains(id));
+            self.channel_filters.retain(|id, _| open.contains(id));
+        }
+    }
+
+    /// Active tasks as seen by `channel_id`: only those matching its
+    /// `subscribe_filter`, if it set one
+    fn channel_tasks(&self, channel_id: u32, include_future: bool) -> Vec<TodoItem> {
+        let mut tasks = self.active_tasks(include_future);
+        if let Some(filter) = self.channel_filters.get(&channel_id) {
+            tasks.retain(|t| filter.matches(t));
+        }
+        tasks
+    }
+
+    /// Send `response` with each channel's `channel_tasks` added as `tasks`.
+    /// A channel whose filter rejects `task` gets a plain `tasks_overview`
+    /// instead, so it never sees frames about tasks outside its view.
+    fn broadcast_with_tasks(&mut self, response: serde_json::Value, task: Option<&TodoItem>) {
+        self.prune_channels();
+        for &channel_id in &self.ws_channels {
+            let filtered_out = task.is_some_and(|task| {
+                self.channel_filters
+                    .get(&channel_id)
+                    .is_some_and(|filter| !filter.matches(task))
+            });
+            let mut frame = if filtered_out {
+                serde_json::json!({ "type": "tasks_overview" })
+            } else {
+                response.clone()
+            };
+            frame["tasks"] = serde_json::json!(self.channel_tasks(channel_id, false));
+            let response_blob = LazyLoadBlob {
+                mime: Some("application/json".to_string()),
+                bytes: frame.to_string().into_bytes(),
+            };
+            send_ws_push(channel_id, WsMessageType::Text, response_blob);
+        }
+    }
+
+    /// Send a JSON frame about `task` to every live channel whose filter,
+    /// if any, matches it
+    fn broadcast_about(&mut self, task: &TodoItem, response: serde_json::Value) {
+        self.prune_channels();
+        if self.ws_channels.is_empty() {
+            return;
+        }
+
+        let response_bytes = response.to_string().into_bytes();
+        for &channel_id in &self.ws_channels {
+            if self
+                .channel_filters
+                .get(&channel_id)
+                .is_some_and(|filter| !filter.matches(task))
+            {
+                continue;
+            }
+            let response_blob = LazyLoadBlob {
+                mime: Some("application/json".to_string()),
+                bytes: response_bytes.clone(),
+            };
+            send_ws_push(channel_id, WsMessageType::Text, response_blob);
+        }
+    }
+
+    /// Push changed tasks to every subscribed peer's `merge_tasks`. Private
+    /// tasks are left out. Each push runs in its own `hyper!` task, so the
+    /// caller never waits on a peer; `record_push` handles each reply, or
+    /// the failure after NOTIFY_TIMEOUT_SECS, when it arrives.
+    fn notify_clients(&mut self, changed: Vec<TodoItem>) {
+        let changed: Vec<TodoItem> = changed.into_iter().filter(|t| t.shared).collect();
+        if changed.is_empty() || self.clients.is_empty() {
+            return;
+        }
+        let request_wrapper = serde_json::json!({
+            "MergeTasks": changed
+        });
+        let body = serde_json::to_vec(&request_wrapper).unwrap();
+        let count = changed.len();
+
+        let state_ptr: *mut TodoState = self;
+        for client in self.clients.clone() {
+            // CRITICAL: Always set expects_response timeout for remote calls
+            let request = Request::new()
+                .target(client.clone())
+                .body(body.clone())
+                .expects_response(NOTIFY_TIMEOUT_SECS);
+            hyperware_app_common::hyper! {
+                let result = hyperware_app_common::send::<Result<(), String>>(request)
+                    .await
+                    .map_err(|e| format!("{:?}", e));
+                // SAFETY: as in `start_reminder_timer`
+                let state = unsafe { &mut *state_ptr };
+                state.record_push(&client, count, result);
+            }
+        }
+    }
+
+    /// Track a subscribed peer's health from the outcome of a push. A peer
+    /// that can't be reached MAX_CLIENT_FAILURES times in a row is dropped;
+    /// a peer that answers, even with a rejection, has its count reset.
+    fn record_push(&mut self, client: &Address, count: usize, result: Result<Result<(), String>, String>) {
+        let key = client.to_string();
+        let send_error = match result {
+            Ok(reply) => {
+                self.client_failures.remove(&key);
+                match reply {
+                    Ok(()) => verbose!(self, "Pushed {} task(s) to {}", count, client),
+                    Err(e) => error!("{} rejected pushed tasks: {}", client, e),
+                }
+                return;
+            }
+            Err(e) => e,
+        };
+
+        let failures = self.client_failures.entry(key.clone()).or_insert(0);
+        *failures += 1;
+        let failures = *failures;
+        error!("Failed to push tasks to {} ({} in a row): {}", client, failures, send_error);
+        if failures >= MAX_CLIENT_FAILURES {
+            error!("Dropping unreachable subscriber {}", client);
+            self.client_failures.remove(&key);
+            self.clients.retain(|c| c != client);
+        }
+    }
+
+    /// Remind open UIs about task `id` at `at` (unix seconds). A task keeps
+    /// only its latest reminder; completing or deleting it cancels that.
+    fn arm_reminder(&mut self, id: &str, at: u64) {
+        self.cancel_reminder(id);
+        self.reminders.push((id.to_string(), at));
+        self.start_reminder_timer(id.to_string(), at);
+    }
+
+    /// Drop the pending reminder for `id`, if any
+    fn cancel_reminder(&mut self, id: &str) {
+        self.reminders.retain(|(task_id, _)| task_id != id);
+    }
+
+    /// Bring reminders in line after the task list was replaced: drop those
+    /// whose task is gone or done, and arm one at the due date for each
+    /// pending task that has none and isn't overdue yet
+    fn reconcile_reminders(&mut self) {
+        let pending: HashSet<&str> = self
+            .tasks
+            .iter()
+            .filter(|t| !t.completed)
+            .map(|t| t.id.as_str())
+            .collect();
+        self.reminders.retain(|(id, _)| pending.contains(id.as_str()));
+
+        let now = self.now();
+        let due: Vec<(String, u64)> = self
+            .tasks
+            .iter()
+            .filter(|t| !t.completed && !self.reminders.iter().any(|(id, _)| *id == t.id))
+            .filter_map(|t| t.due_date.filter(|&at| at > now).map(|at| (t.id.clone(), at)))
+            .collect();
+        for (id, at) in due {
+            self.arm_reminder(&id, at);
+        }
+    }
+
+    /// Sleep on timer:distro:sys until `at`, then fire the reminder. Timers
+    /// can't be withdrawn, so a timer whose reminder was replaced or
+    /// cancelled in the meantime just finds no match when it wakes.
+    fn start_reminder_timer(&mut self, id: String, at: u64) {
+        let delay_ms = at.saturating_sub(self.now()).saturating_mul(1000);
+        let state_ptr: *mut TodoState = self;
+        hyperware_app_common::hyper! {
+            if let Err(e) = hyperware_app_common::sleep(delay_ms).await {
+                error!("Reminder timer for {} failed: {:?}", id, e);
+                return;
+            }
+            // SAFETY: the state lives for the whole process and the runtime
+            // is single-threaded, the same guarantee async handler dispatch
+            // relies on
+            let state = unsafe { &mut *state_ptr };
+            state.fire_reminder(&id, at);
+        }
+    }
+
+    /// Push a `reminder` frame if the reminder for `id` is still the one set
+    /// for `at` and the task is still pending
+    fn fire_reminder(&mut self, id: &str, at: u64) {
+        if !self.reminders.iter().any(|(task_id, when)| task_id == id && *when == at) {
+            return;
+        }
+        self.cancel_reminder(id);
+        let Ok(task) = self.find_task(id) else { return };
+        if task.completed {
+            return;
+        }
+        let task = task.clone();
+        verbose!(self, "Reminder due for task {}", id);
+        let response = serde_json::json!({
+            "type": "reminder",
+            "task": task
+        });
+        self.broadcast_about(&task, response);
+    }
+}
+
+// =============================================================================
+// HYPERPROCESS CONFIGURATION
+// =============================================================================
+
+#[hyperprocess(
+    name = "todo",
+    ui = Some(HttpBindingConfig::default()),
+    // HTTP API endpoints - MUST include /api for frontend communication
+    // Any endpoint referenced in HTTP handlers must be first bound here
+    // No dynamic paths allowed, only static ones
+    endpoints = vec![
+        Binding::Http {
+            path: "/health",
+            config: HttpBindingConfig::new(false, false, false, None),
+        },
+        Binding::Ws {
+            path: "/ws",
+            config: WsBindingConfig::new(false, false, false),
+        },
+        Binding::Http {
+            path: "/api",
+            config: HttpBindingConfig::new(false, false, false, None),
+        },
+    ],
+    // State persistence options:
+    // - EveryMessage: Save after each message (safest, slower)
+    // - OnInterval(n): Save every n seconds
+    // - Never: No automatic saves (manual only)
+    // EveryMessage writes once per WebSocket message, so bursts of UI actions
+    // cost one save each. OnInterval(n) batches them, but anything changed
+    // since the last save is lost if the process stops before the next one;
+    // the framework offers no flush hook for a handler to force a save.
+    save_config = SaveOptions::EveryMessage,
+    wit_world = "todo-template-dot-os-v0"
+)]
+
+// =============================================================================
+// CORE TODO APPLICATION IMPLEMENTATION
+// =============================================================================
+
+impl TodoState {
+    /// Initialize the application state
+    #[init]
+    async fn initialize(&mut self) {
+        verbose!(self, "Initializing todo list state");
+        // Add your app to the Hyperware homepage, named after the list
+        self.register_homepage();
+
+        // Initialize your app state
+        // init can run after persisted state has been loaded, so only reset
+        // what never survives a restart; tasks, clients and settings come
+        // from Default on a fresh start and must be left alone otherwise.
+        self.ws_channels.clear();
+        self.clock_override = None;
+        self.migrate();
+        verbose!(self, "Loaded {} persisted tasks", self.tasks.len());
+        // Timers don't survive a restart; reminders missed while we were
+        // down fire right away
+        for (id, at) in self.reminders.clone() {
+            self.start_reminder_timer(id, at);
+        }
+        // You can use our() to get the address of the current process
+        let our = our();
+        verbose!(self, "Process has just started on here: {}", our);
+    }
+
+    #[local]
+    #[remote]
+    async fn share_tasks(&mut self, request: String) -> Vec<TodoItem> {
+        let source = source();
+        verbose!(self, "Sharing tasks with {}", source);
+        let _value = request;
+        self.shared_tasks()
+    }
+
+    #[local]
+    #[remote]
+    async fn merge_tasks(&mut self, tasks: Vec<TodoItem>) -> Result<(), String> {
+        let source = source();
+        verbose!(self, "Merging tasks with {}", source);
+        let received = tasks.len();
+        let tasks = self.filter_valid(tasks, &source.to_string())?;
+        let accepted = tasks.len();
+        let (inserted, updated) = self.merge_items(tasks);
+        verbose!(
+            self,
+            "Merged tasks from {}: {} inserted, {} updated, {} skipped as invalid",
+            source,
+            inserted,
+            updated,
+            received - accepted
+        );
+        self.broadcast_merged(inserted + updated);
+        Ok(())
+    }
+
+    // Fine-grained sharing: apply a single task pushed by a peer
+    #[remote]
+    async fn accept_task(&mut self, task: TodoItem) -> Result<(), String> {
+        let source = source();
+        let task = self.prepare_item(task)?;
+        verbose!(self, "Accepting task {} from {}", task.id, source);
+        let (inserted, updated) = self.merge_items(vec![task]);
+        notice!(self, "Accepted task: {} inserted, {} updated", inserted, updated);
+        self.broadcast_merged(inserted + updated);
+        Ok(())
+    }
+
+    // Liveness probe for peers: reports our address and clock so the caller
+    // can confirm reachability and measure round-trip latency before syncing
+    #[remote]
+    async fn ping_node(&self) -> String {
+        verbose!(self, "Ping from {}", source());
+        serde_json::json!({
+            "node": our().to_string(),
+            "timestamp": self.now(),
+        })
+        .to_string()
+    }
+
+    // Register the calling node for task updates; subscribing twice is a no-op
+    #[remote]
+    async fn subscribe(&mut self) -> Result<(), String> {
+        let source = source();
+        if self.clients.contains(&source) {
+            verbose!(self, "{} is already subscribed", source);
+            return Ok(());
+        }
+        verbose!(self, "Subscribing {}", source);
+        self.clients.push(source);
+        Ok(())
+    }
+
+    // Stop sending task updates to the calling node
+    #[remote]
+    async fn unsubscribe(&mut self) -> Result<(), String> {
+        let source = source();
+        self.clients.retain(|client| *client != source);
+        self.client_failures.remove(&source.to_string());
+        verbose!(self, "Unsubscribed {}", source);
+        Ok(())
+    }
+
+    #[remote]
+    async fn remote_task_count(&self, request: String) -> u64 {
+        let _value = request;
+        self.tasks.iter().filter(|t| t.shared).count() as u64
+    }
+
+    // HTTP ENDPOINT WITH PARAMETERS
+    // Parameters are sent as either:
+    // - Single value: { "MethodName": value }
+    // - Multiple values as tuple: { "MethodName": [val1, val2] }
+    // Tasks whose start date is still in the future are left out unless
+    // the request is "all"
+    #[http]
+    async fn get_tasks(&self, request: String) -> Result<Vec<TodoItem>, TodoError> {
+        verbose!(self, "Request: {:?}", request);
+        verbose!(self, "Fetching tasks");
+        Ok(self.active_tasks(request.trim() == "all"))
+    }
+
+    // Move a task to the trash and return it so the frontend can confirm
+    // what was deleted; `restore_task` brings it back
+    #[local]
+    #[http]
+    async fn delete_task(&mut self, id: String) -> Result<TodoItem, TodoError> {
+        let removed = self.remove_task(&id)?;
+        verbose!(self, "Deleted task {}", removed.id);
+        self.broadcast_tasks("tasks_overview");
+        Ok(removed)
+    }
+
+    // Deleted tasks, oldest first
+    #[http]
+    async fn get_trash(&self, request: String) -> Result<Vec<TodoItem>, TodoError> {
+        let _value = request;
+        Ok(self.trash.clone())
+    }
+
+    // Move a task out of the trash and back to the end of the list
+    #[local]
+    #[http]
+    async fn restore_task(&mut self, id: String) -> Result<TodoItem, TodoError> {
+        if self.find_task(&id).is_ok() {
+            return Err(TodoError::new(
+                TodoErrorCode::Conflict,
+                format!("A task with id '{}' already exists", id),
+            ));
+        }
+        let index = self
+            .trash
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| {
+                TodoError::new(
+                    TodoErrorCode::NotFound,
+                    format!("Task with id '{}' is not in the trash", id),
+                )
+            })?;
+        let mut task = self.trash.remove(index);
+        task.updated_at = self.now();
+        self.tasks.push(task.clone());
+        verbose!(self, "Restored task {}", task.id);
+        self.broadcast_task("task_added", &task);
+        Ok(task)
+    }
+
+    // Permanently delete everything in the trash, returning how many went
+    #[local]
+    #[http]
+    async fn empty_trash(&mut self, request: String) -> Result<usize, TodoError> {
+        let _value = request;
+        let purged = self.trash.len();
+        self.trash.clear();
+        verbose!(self, "Emptied trash of {} tasks", purged);
+        Ok(purged)
+    }
+
+    // Fix a task's text; args are (id, new_text)
+    #[http]
+    async fn edit_task(&mut self, args: (String, String)) -> Result<TodoItem, TodoError> {
+        let (id, new_text) = args;
+        let text = self.prepare_text(&new_text)?;
+        let task = self.touch_task(&id)?;
+        task.text = text;
+        let task = task.clone();
+        self.broadcast_task("task_edited", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Tasks with a short display number so users can say "task 3" instead
+    // of quoting a UUID
+    #[http]
+    async fn get_tasks_numbered(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let numbered: Vec<serde_json::Value> = self
+            .numbered_tasks()
+            .map(|(number, task)| serde_json::json!({ "number": number, "task": task }))
+            .collect();
+        serde_json::to_string(&numbered).map_err(|e| format!("Failed to serialize tasks: {}", e).into())
+    }
+
+    // Text interface for terminals and chat bots, e.g. "add buy milk",
+    // "done 3", "rm 3" or "list". Tasks are referenced by display number.
+    #[http]
+    async fn command(&mut self, line: String) -> Result<String, TodoError> {
+        let line = line.trim();
+        let (cmd, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match cmd.to_lowercase().as_str() {
+            "add" => {
+                let task = self.create_task(rest, None, None)?;
+                self.broadcast_tasks("tasks_overview");
+                self.notify_clients(vec![task.clone()]);
+                Ok(format!("Added {}: {}", self.tasks.len(), task.text))
+            }
+            "done" => {
+                let id = self.id_for_number(rest)?;
+                if let Ok(task) = self.find_task(&id) {
+                    if task.completed {
+                        return Ok(format!("Already done: {}", task.text));
+                    }
+                }
+                let (task, ancestors) = self.set_completed(&id, true)?;
+                self.broadcast_tasks("tasks_overview");
+                self.check_inbox_zero();
+                let text = format!("Done: {}", task.text);
+                self.notify_clients([vec![task], ancestors].concat());
+                Ok(text)
+            }
+            "rm" => {
+                let id = self.id_for_number(rest)?;
+                let removed = self.remove_task(&id)?;
+                self.broadcast_tasks("tasks_overview");
+                Ok(format!("Removed: {}", removed.text))
+            }
+            "list" => {
+                if self.tasks.is_empty() {
+                    return Ok("No tasks".to_string());
+                }
+                Ok(self
+                    .numbered_tasks()
+                    .map(|(n, t)| format!("{}. [{}] {}", n, if t.completed { "x" } else { " " }, t.text))
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            _ => Err(format!("Unknown command '{}'. {}", cmd, COMMAND_USAGE).into()),
+        }
+    }
+
+    // Create a task with an optional deadline; args are (text, due_date)
+    #[http]
+    async fn add_task_with_due(&mut self, args: (String, Option<u64>)) -> Result<TodoItem, TodoError> {
+        let (text, due_date) = args;
+        let task = self.create_task(&text, None, due_date)?;
+        self.broadcast_task("task_added", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Push a `reminder` frame to open UIs at `at_unix`; args are (id, at_unix).
+    // Replaces any earlier reminder for the task.
+    #[http]
+    async fn set_reminder(&mut self, args: (String, u64)) -> Result<(), TodoError> {
+        let (id, at_unix) = args;
+        if self.find_task(&id)?.completed {
+            return Err(format!("Task with id '{}' is already completed", id).into());
+        }
+        self.arm_reminder(&id, at_unix);
+        Ok(())
+    }
+
+    // Set or clear the "not before" date; args are (id, start_date)
+    #[http]
+    async fn set_start_date(&mut self, args: (String, Option<u64>)) -> Result<TodoItem, TodoError> {
+        let (id, start_date) = args;
+        let task = self.touch_task(&id)?;
+        task.start_date = start_date;
+        let task = task.clone();
+        self.broadcast_task("task_edited", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Tasks carrying `tag`, compared case-insensitively
+    #[http]
+    async fn tasks_with_tag(&self, tag: String) -> Result<Vec<TodoItem>, TodoError> {
+        let tag = normalize_tag(&tag)?;
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| t.tags.iter().any(|existing| existing.to_lowercase() == tag))
+            .cloned()
+            .collect())
+    }
+
+    // Case-insensitive substring search over task text and tags, in list
+    // order. An empty query returns every task.
+    #[local]
+    #[http]
+    async fn search_tasks(&self, query: String) -> Result<Vec<TodoItem>, TodoError> {
+        let query = query.trim().to_lowercase();
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| {
+                t.text.to_lowercase().contains(&query)
+                    || t.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+            })
+            .cloned()
+            .collect())
+    }
+
+    // Add a checklist item under an existing task; args are (parent_id, text).
+    // Once every subtask is done the parent completes automatically.
+    #[local]
+    #[http]
+    async fn add_subtask(&mut self, args: (String, String)) -> Result<TodoItem, TodoError> {
+        let (parent_id, text) = args;
+        self.find_task(&parent_id)?;
+        let id = self.create_task(&text, None, None)?.id;
+        let task = self.find_task_mut(&id)?;
+        task.parent_id = Some(parent_id);
+        let task = task.clone();
+        self.broadcast_task("task_added", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Attach a tag to a task; args are (id, tag). Adding an existing tag is a no-op.
+    #[http]
+    async fn add_tag(&mut self, args: (String, String)) -> Result<TodoItem, TodoError> {
+        let (id, tag) = args;
+        let tag = normalize_tag(&tag)?;
+        let task = self.touch_task(&id)?;
+        if !task.tags.iter().any(|existing| existing.to_lowercase() == tag) {
+            task.tags.push(tag);
+        }
+        let task = task.clone();
+        self.broadcast_task("task_edited", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Flip completion on each listed task and return the updated items.
+    // Unknown ids are skipped; open UIs get one tasks_overview for the batch.
+    #[local]
+    #[http]
+    async fn toggle_many(&mut self, ids: Vec<String>) -> Result<Vec<TodoItem>, TodoError> {
+        let mut toggled = Vec::new();
+        let mut ancestors = Vec::new();
+        for id in &ids {
+            let Ok(completed) = self.find_task(id).map(|t| !t.completed) else {
+                continue;
+            };
+            let (task, completed_ancestors) = self.set_completed(id, completed)?;
+            toggled.push(task);
+            ancestors.extend(completed_ancestors);
+        }
+        if toggled.is_empty() {
+            return Ok(toggled);
+        }
+
+        verbose!(self, "Toggled {} of {} requested tasks", toggled.len(), ids.len());
+        self.broadcast_tasks("tasks_overview");
+        if toggled.iter().any(|t| t.completed) {
+            self.check_inbox_zero();
+        }
+        self.notify_clients([toggled.clone(), ancestors].concat());
+        Ok(toggled)
+    }
+
+    // "Select all": mark every task done (true) or pending (false).
+    // Returns how many changed state; 0 when all already match.
+    #[http]
+    async fn set_all_completed(&mut self, completed: bool) -> Result<usize, TodoError> {
+        let ids: Vec<String> = self
+            .tasks
+            .iter()
+            .filter(|t| t.completed != completed)
+            .map(|t| t.id.clone())
+            .collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut changed = Vec::with_capacity(ids.len());
+        let mut ancestors = Vec::new();
+        for id in &ids {
+            let (task, completed_ancestors) = self.set_completed(id, completed)?;
+            changed.push(task);
+            ancestors.extend(completed_ancestors);
+        }
+        verbose!(self, "Marked {} tasks as {}", changed.len(), if completed { "done" } else { "pending" });
+        self.broadcast_tasks("tasks_overview");
+        if completed {
+            self.check_inbox_zero();
+        }
+        let count = changed.len();
+        self.notify_clients([changed, ancestors].concat());
+        Ok(count)
+    }
+
+    // Bulk cleanup: move every completed task to the trash, returning how
+    // many went
+    #[local]
+    #[http]
+    async fn clear_completed(&mut self, request: String) -> Result<usize, TodoError> {
+        let _value = request;
+        Ok(self.remove_completed())
+    }
+
+    // Repair tool: give fresh ids to every task sharing an id with an
+    // earlier one. Returns how many tasks were re-keyed.
+    #[http]
+    async fn fix_duplicate_ids(&mut self, request: String) -> Result<u32, TodoError> {
+        let _value = request;
+        let mut seen = HashSet::new();
+        let duplicates: Vec<usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| !seen.insert(t.id.clone()))
+            .map(|(i, _)| i)
+            .collect();
+
+        let now = self.now();
+        for &i in &duplicates {
+            let id = self.next_id();
+            error!("Reassigning duplicate task id {} to {}", self.tasks[i].id, id);
+            self.tasks[i].id = id;
+            self.tasks[i].updated_at = now;
+        }
+        if !duplicates.is_empty() {
+            self.broadcast_tasks("tasks_overview");
+        }
+        Ok(duplicates.len() as u32)
+    }
+
+    // Make a task repeat every `interval` seconds, or stop it repeating with
+    // None; args are (id, interval)
+    #[http]
+    async fn set_recurrence(&mut self, args: (String, Option<u64>)) -> Result<TodoItem, TodoError> {
+        let (id, recurrence) = args;
+        if recurrence == Some(0) {
+            return Err("Recurrence interval must be at least one second".to_string().into());
+        }
+        let task = self.touch_task(&id)?;
+        task.recurrence = recurrence;
+        let task = task.clone();
+        self.broadcast_task("task_edited", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Set or clear a task's effort estimate; args are (id, minutes)
+    #[http]
+    async fn set_estimate(&mut self, args: (String, Option<u32>)) -> Result<TodoItem, TodoError> {
+        let (id, estimate_mins) = args;
+        let task = self.touch_task(&id)?;
+        task.estimate_mins = estimate_mins;
+        let task = task.clone();
+        self.broadcast_task("task_edited", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Minutes of work per day used to project completion; 0 turns it off
+    #[http]
+    async fn set_daily_capacity(&mut self, minutes: u32) -> Result<(), TodoError> {
+        self.daily_capacity_mins = minutes;
+        Ok(())
+    }
+
+    // Remaining estimated work across pending tasks. Tasks without an
+    // estimate are listed in `unestimated` rather than counted. With a daily
+    // capacity set, also projects the completion date (YYYY-MM-DD, UTC).
+    #[http]
+    async fn estimate_remaining(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let pending = self.tasks.iter().filter(|t| !t.completed);
+        let (estimated, unestimated): (Vec<&TodoItem>, Vec<&TodoItem>) =
+            pending.partition(|t| t.estimate_mins.is_some());
+        let remaining_mins: u64 = estimated
+            .iter()
+            .filter_map(|t| t.estimate_mins)
+            .map(u64::from)
+            .sum();
+
+        let projected_completion = (self.daily_capacity_mins > 0).then(|| {
+            let days = remaining_mins.div_ceil(u64::from(self.daily_capacity_mins));
+            format_date(self.now() + days * 86_400)
+        });
+
+        Ok(serde_json::json!({
+            "remaining_mins": remaining_mins,
+            "estimated_tasks": estimated.len(),
+            "unestimated": unestimated.iter().map(|t| &t.id).collect::<Vec<_>>(),
+            "daily_capacity_mins": self.daily_capacity_mins,
+            "projected_completion": projected_completion,
+        })
+        .to_string())
+    }
+
+    // Counts for a dashboard widget, as JSON since per-priority and per-tag
+    // maps aren't WIT-safe. Tags are counted case-insensitively, and the
+    // completion percentage is 0 for an empty list.
+    #[local]
+    #[http]
+    async fn stats(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let total = self.tasks.len();
+        let completed = self.tasks.iter().filter(|t| t.completed).count();
+        let completion_pct = if total == 0 {
+            0.0
+        } else {
+            completed as f64 * 100.0 / total as f64
+        };
+
+        let mut by_priority: BTreeMap<u8, usize> = BTreeMap::new();
+        let mut by_tag: BTreeMap<String, usize> = BTreeMap::new();
+        for task in &self.tasks {
+            *by_priority.entry(task.priority).or_default() += 1;
+            for tag in &task.tags {
+                *by_tag.entry(tag.to_lowercase()).or_default() += 1;
+            }
+        }
+
+        Ok(serde_json::json!({
+            "total": total,
+            "completed": completed,
+            "incomplete": total - completed,
+            "completion_pct": completion_pct,
+            "by_priority": by_priority,
+            "by_tag": by_tag,
+        })
+        .to_string())
+    }
+
+    // Tasks changed after `since` (unix seconds), for incremental sync
+    #[http]
+    async fn tasks_modified_since(&self, since: u64) -> Result<Vec<TodoItem>, TodoError> {
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| t.updated_at > since)
+            .cloned()
+            .collect())
+    }
+
+    // Tasks completed between `start` and `end` (unix seconds, inclusive);
+    // tasks without a completion time are left out
+    #[http]
+    async fn get_completed_between(&self, start: u64, end: u64) -> Result<Vec<TodoItem>, TodoError> {
+        if start > end {
+            return Err(format!("Start {} is after end {}", start, end).into());
+        }
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| t.completed && t.completed_at.is_some_and(|at| (start..=end).contains(&at)))
+            .cloned()
+            .collect())
+    }
+
+    // Incomplete tasks whose deadline is before `now` (unix seconds)
+    #[http]
+    async fn overdue_tasks(&self, now: u64) -> Result<Vec<TodoItem>, TodoError> {
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| !t.completed && t.due_date.is_some_and(|due| due < now))
+            .cloned()
+            .collect())
+    }
+
+    // Highest priority first; equal priorities keep their list order
+    #[http]
+    async fn tasks_by_priority(&self, request: String) -> Result<Vec<TodoItem>, TodoError> {
+        let _value = request;
+        let mut tasks = self.tasks.clone();
+        tasks.sort_by(|a, b| b.priority.cmp(&a.priority));
+        Ok(tasks)
+    }
+
+    // Filtered read; body is a JSON filter object such as
+    // { "completed": false, "text_contains": "milk" }
+    #[http]
+    async fn query(&self, filter_json: String) -> Result<String, TodoError> {
+        let filter: TaskFilter = serde_json::from_str(&filter_json)
+            .map_err(|e| format!("Invalid filter: {}", e))?;
+        let matches: Vec<&TodoItem> = self.tasks.iter().filter(|t| filter.matches(t)).collect();
+        verbose!(self, "Query matched {} of {} tasks", matches.len(), self.tasks.len());
+        serde_json::to_string(&matches).map_err(|e| format!("Failed to serialize tasks: {}", e).into())
+    }
+
+    // Store a labeled copy of the current list; reusing a label replaces it
+    #[http]
+    async fn create_snapshot(&mut self, label: String) -> Result<String, TodoError> {
+        let label = label.trim().to_string();
+        if label.is_empty() {
+            return Err("Snapshot label cannot be empty".to_string().into());
+        }
+        if label.chars().count() > MAX_SNAPSHOT_LABEL_LEN {
+            return Err(format!(
+                "Snapshot label exceeds {} characters",
+                MAX_SNAPSHOT_LABEL_LEN
+            )
+            .into());
+        }
+        if self.tasks.len() > MAX_SNAPSHOT_TASKS {
+            return Err(format!(
+                "Cannot snapshot more than {} tasks",
+                MAX_SNAPSHOT_TASKS
+            )
+            .into());
+        }
+
+        let created_at = self.now();
+        self.snapshots.retain(|(l, _, _)| *l != label);
+        self.snapshots.push((label.clone(), created_at, self.tasks.clone()));
+
+        // Evict oldest snapshots until both the count and size caps hold
+        while self.snapshots.len() > MAX_SNAPSHOTS
+            || self.snapshots.iter().map(|(_, _, t)| t.len()).sum::<usize>() > MAX_SNAPSHOT_TASKS
+        {
+            let (evicted, _, _) = self.snapshots.remove(0);
+            verbose!(self, "Evicted snapshot '{}'", evicted);
+        }
+
+        Ok(serde_json::json!({
+            "label": label,
+            "created_at": created_at,
+            "tasks": self.tasks.len(),
+        })
+        .to_string())
+    }
+
+    // Roll the list back to a previously created snapshot. Restored tasks
+    // leave the trash, and reminders follow the restored list.
+    #[http]
+    async fn restore_snapshot(&mut self, label: String) -> Result<Vec<TodoItem>, TodoError> {
+        let label = label.trim();
+        let tasks = self
+            .snapshots
+            .iter()
+            .find(|(l, _, _)| l == label)
+            .map(|(_, _, tasks)| tasks.clone())
+            .ok_or_else(|| {
+                TodoError::new(TodoErrorCode::NotFound, format!("Snapshot '{}' not found", label))
+            })?;
+        verbose!(self, "Restoring snapshot '{}' ({} tasks)", label, tasks.len());
+        self.tasks = tasks;
+        // A restored task is live again, so it can't also sit in the trash
+        let restored: HashSet<&str> = self.tasks.iter().map(|t| t.id.as_str()).collect();
+        self.trash.retain(|t| !restored.contains(t.id.as_str()));
+        self.reconcile_reminders();
+        self.broadcast_tasks("tasks_overview");
+        Ok(self.tasks.clone())
+    }
+
+    // Hand the shared tasks to another node running this app, e.g. before
+    // decommissioning this one. Once the peer confirms the merge, the tasks
+    // its share_tasks now lists go to the trash here; anything it skipped
+    // stays, so a failed or partial transfer loses nothing. Private tasks
+    // are never sent and stay here.
+    #[http]
+    async fn transfer_all(&mut self, target_node: String) -> Result<String, TodoError> {
+        let target_node = target_node.trim().to_string();
+        if target_node.is_empty() {
+            return Err("Target node cannot be empty".to_string().into());
+        }
+        if target_node == our().node {
+            return Err("Cannot transfer tasks to this node".to_string().into());
+        }
+
+        let target_address = Address::new(target_node.clone(), our().process.clone());
+        let sent = self.shared_tasks();
+        let request_wrapper = serde_json::json!({
+            "MergeTasks": sent
+        });
+
+        // CRITICAL: Always set expects_response timeout for remote calls
+        let response = Request::new()
+            .target(target_address.clone())
+            .body(serde_json::to_vec(&request_wrapper).unwrap())
+            .expects_response(30)
+            .send_and_await_response(30)
+            .map_err(|e| format!("Failed to send tasks to {}: {:?}", target_node, e))?
+            .map_err(|e| format!("Failed to send tasks to {}: {:?}", target_node, e))?;
+
+        serde_json::from_slice::<Result<(), String>>(response.body())
+            .map_err(|e| format!("Unexpected response from {}: {}", target_node, e))?
+            .map_err(|e| format!("{} rejected the transfer: {}", target_node, e))?;
+
+        // The peer drops invalid tasks and ones in its trash, so ask what
+        // it holds now instead of trusting the merge
+        let request_wrapper = serde_json::json!({
+            "ShareTasks": ""
+        });
+        let response = Request::new()
+            .target(target_address)
+            .body(serde_json::to_vec(&request_wrapper).unwrap())
+            .expects_response(30)
+            .send_and_await_response(30)
+            .map_err(|e| format!("Failed to confirm transfer with {}: {:?}", target_node, e))?
+            .map_err(|e| format!("Failed to confirm transfer with {}: {:?}", target_node, e))?;
+        let held: HashSet<String> = serde_json::from_slice::<Vec<TodoItem>>(response.body())
+            .map_err(|e| format!("Unexpected response from {}: {}", target_node, e))?
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+
+        let (transferred, kept): (Vec<TodoItem>, Vec<TodoItem>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|t| t.shared && held.contains(&t.id));
+        self.tasks = kept;
+        let count = transferred.len();
+        self.move_to_trash(transferred);
+        self.broadcast_tasks("tasks_overview");
+        verbose!(self, "Transferred {} of {} tasks to {}", count, sent.len(), target_node);
+
+        Ok(serde_json::json!({
+            "target": target_node,
+            "transferred": count,
+            "unconfirmed": sent.len().saturating_sub(count),
+        })
+        .to_string())
+    }
+
+    // Pin the clock used for timestamps so tests get deterministic values;
+    // pass None to go back to the real clock. Simulation mode only.
+    #[local]
+    async fn set_clock(&mut self, now: Option<u64>) -> Result<(), TodoError> {
+        if !cfg!(feature = "simulation-mode") {
+            return Err("set_clock is only available in simulation mode".to_string().into());
+        }
+        self.clock_override = now;
+        Ok(())
+    }
+
+    // Network-wide view of a shared list: asks every registered client for
+    // its task count. Unreachable peers are reported, not treated as fatal.
+    #[http]
+    async fn aggregate_from_peers(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let local = self.tasks.len() as u64;
+        let mut total = local;
+        let mut peers = Vec::new();
+
+        for client in &self.clients {
+            let request_wrapper = serde_json::json!({
+                "RemoteTaskCount": ""
+            });
+            let result = Request::new()
+                .target(client.clone())
+                .body(serde_json::to_vec(&request_wrapper).unwrap())
+                .expects_response(30)
+                .send_and_await_response(30);
+
+            let count = match result {
+                Ok(Ok(response)) => serde_json::from_slice::<u64>(response.body())
+                    .map_err(|e| format!("Unexpected response: {}", e)),
+                Ok(Err(e)) => Err(format!("{:?}", e)),
+                Err(e) => Err(format!("{:?}", e)),
+            };
+            match count {
+                Ok(count) => {
+                    total += count;
+                    peers.push(serde_json::json!({ "node": client.node, "count": count }));
+                }
+                Err(e) => {
+                    error!("Failed to get task count from {}: {}", client, e);
+                    peers.push(serde_json::json!({ "node": client.node, "error": e }));
+                }
+            }
+        }
+
+        Ok(serde_json::json!({
+            "local": local,
+            "total": total,
+            "peers": peers,
+        })
+        .to_string())
+    }
+
+    // Printable HTML rendering of the list, separate from the interactive UI.
+    // Tasks are grouped by tag; a task with several tags appears under each.
+    #[http]
+    async fn export_print(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let mut tags: Vec<&String> = self.tasks.iter().flat_map(|t| &t.tags).collect();
+        tags.sort();
+        tags.dedup();
+        let mut groups: Vec<(Option<&String>, Vec<&TodoItem>)> = tags
+            .into_iter()
+            .map(|tag| (Some(tag), self.tasks.iter().filter(|t| t.tags.contains(tag)).collect()))
+            .collect();
+        let untagged: Vec<&TodoItem> = self.tasks.iter().filter(|t| t.tags.is_empty()).collect();
+        if !untagged.is_empty() || groups.is_empty() {
+            groups.push((None, untagged));
+        }
+        // A list without any tags prints as one plain checklist
+        let headings = groups.iter().any(|(tag, _)| tag.is_some());
+
+        let title = if self.list_title.is_empty() {
+            "Todo List".to_string()
+        } else {
+            escape_html(&self.list_title)
+        };
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>li {{ list-style: none; }} .done {{ text-decoration: line-through; }}</style>\n\
+             </head>\n<body>\n<h1>{title}</h1>\n",
+        );
+        if !self.list_description.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(&self.list_description)));
+        }
+        for (tag, tasks) in groups {
+            if headings {
+                let heading = tag.map(|t| escape_html(t)).unwrap_or_else(|| "Untagged".to_string());
+                html.push_str(&format!("<h2>{}</h2>\n", heading));
+            }
+            html.push_str("<ul>\n");
+            for task in tasks {
+                html.push_str(&print_item(task));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        Ok(html)
+    }
+
+    // Full backup (tasks and settings) for migrating or restoring a node
+    #[local]
+    #[http]
+    async fn export_full(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let export = FullExport {
+            schema_version: self.schema_version,
+            tasks: self.tasks.clone(),
+            log_level: self.log_level,
+            text_format: self.text_format,
+            collapse_whitespace: self.collapse_whitespace,
+            mute_inbox_zero: self.mute_inbox_zero,
+            strict_remote_validation: self.strict_remote_validation,
+            private_by_default: self.private_by_default,
+            reject_duplicates: self.reject_duplicates,
+            daily_capacity_mins: self.daily_capacity_mins,
+            list_title: self.list_title.clone(),
+            list_description: self.list_description.clone(),
+            trash: self.trash.clone(),
+        };
+        serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize export: {}", e).into())
+    }
+
+    // Tasks only, as a self-describing JSON document for the UI to offer as
+    // a file download; an empty list still yields a valid document
+    #[local]
+    #[http]
+    async fn export_tasks(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        let export = TasksExport {
+            version: self.schema_version,
+            exported_at: self.now(),
+            list_title: self.list_title.clone(),
+            tasks: self.tasks.clone(),
+        };
+        serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize export: {}", e).into())
+    }
+
+    // Merge an `export_tasks` document into the list by id, with the same
+    // conflict rules as `merge_tasks`. Any invalid item rejects the whole
+    // document. Returns how many tasks were inserted or updated.
+    #[local]
+    #[http]
+    async fn import_tasks(&mut self, doc: String) -> Result<usize, TodoError> {
+        let import: TasksExport =
+            serde_json::from_str(&doc).map_err(|e| format!("Invalid import: {e}"))?;
+        if import.version > SCHEMA_VERSION {
+            return Err(TodoError::new(
+                TodoErrorCode::Conflict,
+                format!(
+                    "Document version {} is newer than supported version {}",
+                    import.version, SCHEMA_VERSION
+                ),
+            ));
+        }
+        let tasks = import
+            .tasks
+            .into_iter()
+            .enumerate()
+            .map(|(index, task)| {
+                self.prepare_item(task)
+                    .map_err(|e| format!("Task at index {} is invalid: {}", index, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (inserted, updated) = self.merge_items(tasks);
+        verbose!(self, "Imported tasks: {} inserted, {} updated", inserted, updated);
+        self.broadcast_tasks("tasks_overview");
+        Ok(inserted + updated)
+    }
+
+    // Replace tasks and settings with an `export_full` backup; older
+    // schema versions are migrated, newer ones refused. Every task is
+    // cleaned and validated first, so a bad backup leaves the current state
+    // untouched.
+    #[local]
+    #[http]
+    async fn import_full(&mut self, json: String) -> Result<(), TodoError> {
+        let mut import: FullExport =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid import: {}", e))?;
+        if import.schema_version > SCHEMA_VERSION {
+            return Err(TodoError::new(
+                TodoErrorCode::Conflict,
+                format!(
+                    "Backup schema version {} is newer than supported version {}",
+                    import.schema_version, SCHEMA_VERSION
+                ),
+            ));
+        }
+        let list_title = validate_list_field(&import.list_title, "title", MAX_LIST_TITLE_LEN)?;
+        let list_description =
+            validate_list_field(&import.list_description, "description", MAX_LIST_DESCRIPTION_LEN)?;
+        // Text is cleaned under the backup's own settings, which replace ours
+        for (list, tasks) in [("tasks", &mut import.tasks), ("trash", &mut import.trash)] {
+            let mut seen = HashSet::new();
+            for (index, task) in tasks.iter_mut().enumerate() {
+                let invalid = |e: String| format!("Task at {} index {} is invalid: {}", list, index, e);
+                task.text = prepare_text_for(&task.text, import.text_format, import.collapse_whitespace)
+                    .map_err(|e| invalid(e.into()))?;
+                validate_item(task).map_err(invalid)?;
+                if !seen.insert(task.id.clone()) {
+                    return Err(TodoError::new(
+                        TodoErrorCode::InvalidId,
+                        format!("Duplicate task id '{}' in {}", task.id, list),
+                    ));
+                }
+            }
+        }
+
+        self.schema_version = import.schema_version;
+        self.tasks = import.tasks;
+        self.log_level = import.log_level;
+        self.text_format = import.text_format;
+        self.collapse_whitespace = import.collapse_whitespace;
+        self.mute_inbox_zero = import.mute_inbox_zero;
+        self.strict_remote_validation = import.strict_remote_validation;
+        self.private_by_default = import.private_by_default;
+        self.reject_duplicates = import.reject_duplicates;
+        self.daily_capacity_mins = import.daily_capacity_mins;
+        self.list_title = list_title;
+        self.list_description = list_description;
+        self.trash = import.trash;
+        // Reminders belonged to the replaced list; start over from due dates
+        self.reminders.clear();
+        self.reconcile_reminders();
+        self.migrate();
+        self.register_homepage();
+        verbose!(self, "Imported full backup with {} tasks", self.tasks.len());
+        self.broadcast_tasks("tasks_overview");
+        Ok(())
+    }
+
+    // Describes TodoItem's fields (type, optional, limits) for dynamic forms;
+    // timestamps are unix seconds
+    #[http]
+    async fn get_task_schema(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        Ok(todo_item_schema().to_string())
+    }
+
+    // Liveness and basic metrics probe for ops, backing the /health binding
+    #[http(method = "GET", path = "/health")]
+    async fn health(&self) -> Result<String, TodoError> {
+        Ok(serde_json::json!({
+            "status": "ok",
+            "tasks": self.tasks.len(),
+            "ws_channels": self.ws_channels.len(),
+            "address": our().to_string(),
+        })
+        .to_string())
+    }
+
+    // Lets clients and peers check compatibility before interacting
+    #[http]
+    async fn get_version_info(&self, request: String) -> Result<String, TodoError> {
+        let _value = request;
+        Ok(serde_json::json!({
+            "wit_world": WIT_WORLD,
+            "version": env!("CARGO_PKG_VERSION"),
+            "schema_version": SCHEMA_VERSION,
+        })
+        .to_string())
+    }
+
+    // Accepts "plain" (default) or "markdown"; applies to text saved afterwards
+    #[local]
+    #[http]
+    async fn set_text_format(&mut self, format: String) -> Result<(), TodoError> {
+        self.text_format = format.parse()?;
+        Ok(())
+    }
+
+    // When on, tabs, newlines and repeated spaces in saved text become one space
+    #[local]
+    #[http]
+    async fn set_collapse_whitespace(&mut self, enabled: bool) -> Result<(), TodoError> {
+        self.collapse_whitespace = enabled;
+        Ok(())
+    }
+
+    // Drag-and-drop ordering: tasks follow `ordered_ids`, unknown ids are
+    // ignored and unlisted tasks keep their order at the end
+    #[local]
+    #[http]
+    async fn reorder_tasks(&mut self, ordered_ids: Vec<String>) -> Result<(), TodoError> {
+        self.reorder(&ordered_ids);
+        self.broadcast_tasks("tasks_overview");
+        Ok(())
+    }
+
+    // Swap the list positions of two tasks in one step
+    #[http]
+    async fn swap_tasks(&mut self, id_a: String, id_b: String) -> Result<(), TodoError> {
+        let a = self
+            .tasks
+            .iter()
+            .position(|t| t.id == id_a)
+            .ok_or_else(|| TodoError::not_found(&id_a))?;
+        let b = self
+            .tasks
+            .iter()
+            .position(|t| t.id == id_b)
+            .ok_or_else(|| TodoError::not_found(&id_b))?;
+        self.tasks.swap(a, b);
+        self.broadcast_tasks("tasks_overview");
+        Ok(())
+    }
+
+    // Set or clear a task's icon; args are (id, icon)
+    #[http]
+    async fn set_icon(&mut self, args: (String, Option<String>)) -> Result<TodoItem, TodoError> {
+        let (id, icon) = args;
+        let icon = match icon.map(|i| i.trim().to_string()) {
+            Some(i) if i.is_empty() => None,
+            Some(i) if i.chars().count() > MAX_ICON_LEN => {
+                return Err(format!("Icon exceeds {} characters", MAX_ICON_LEN).into());
+            }
+            other => other,
+        };
+        let task = self.touch_task(&id)?;
+        task.icon = icon;
+        let task = task.clone();
+        self.broadcast_tasks("tasks_overview");
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Name the list (e.g. "Q3 Roadmap"); an empty title reverts to the default
+    #[local]
+    #[http]
+    async fn set_list_title(&mut self, title: String) -> Result<(), TodoError> {
+        self.list_title = validate_list_field(&title, "title", MAX_LIST_TITLE_LEN)?;
+        self.register_homepage();
+        Ok(())
+    }
+
+    // Short note about the list, shown in the homepage widget
+    #[http]
+    async fn set_list_description(&mut self, description: String) -> Result<(), TodoError> {
+        self.list_description =
+            validate_list_field(&description, "description", MAX_LIST_DESCRIPTION_LEN)?;
+        self.register_homepage();
+        Ok(())
+    }
+
+    // Make a task visible to peers (true) or keep it on this node (false)
+    #[local]
+    #[http]
+    async fn set_shared(&mut self, id: String, on: bool) -> Result<TodoItem, TodoError> {
+        let task = self.touch_task(&id)?;
+        task.shared = on;
+        let task = task.clone();
+        self.broadcast_task("task_edited", &task);
+        self.notify_clients(vec![task.clone()]);
+        Ok(task)
+    }
+
+    // Whether tasks created from now on start shared (default) or private
+    #[http]
+    async fn set_share_by_default(&mut self, enabled: bool) -> Result<(), TodoError> {
+        self.private_by_default = !enabled;
+        Ok(())
+    }
+
+    // Refuse new tasks whose text matches a pending one (off by default).
+    // Completed tasks don't count, so a done chore can be added again.
+    #[local]
+    #[http]
+    async fn set_reject_duplicates(&mut self, enabled: bool) -> Result<(), TodoError> {
+        self.reject_duplicates = enabled;
+        Ok(())
+    }
+
+    // Toggle the celebration frame pushed when the last pending task is done
+    #[http]
+    async fn set_inbox_zero_push(&mut self, enabled: bool) -> Result<(), TodoError> {
+        self.mute_inbox_zero = !enabled;
+        Ok(())
+    }
+
+    // Strict: one invalid task rejects a whole merge batch.
+    // Lenient (default): invalid tasks are skipped and logged.
+    #[local]
+    #[http]
+    async fn set_strict_remote_validation(&mut self, strict: bool) -> Result<(), TodoError> {
+        self.strict_remote_validation = strict;
+        Ok(())
+    }
+
+    // Accepts "error", "info" or "debug" (case-insensitive)
+    #[http]
+    async fn set_log_level(&mut self, level: String) -> Result<(), TodoError> {
+        self.log_level = level.parse()?;
+        Ok(())
+    }
+
+    // WEBSOCKET ENDPOINT
+    // WebSocket messages are sent as JSON blobs
+    // The message type is specified in the WsMessageType enum
+    // The blob contains the message data
+    #[ws]
+    fn websocket(&mut self, channel_id: u32, message_type: WsMessageType, blob: LazyLoadBlob) {
+        // The macro gives us no open event, so a channel is registered the
+        // first time it sends anything; Close below unregisters it again.
+        // Any message counts as a heartbeat, since browsers can't send ping
+        // frames.
+        if !matches!(message_type, WsMessageType::Close) {
+            self.ws_channels.insert(channel_id);
+            let now = self.now();
+            self.last_seen.insert(channel_id, now);
+        }
+
+        // Drop channels that went quiet (e.g. a tab that vanished without
+        // sending Close)
+        self.reap_idle_channels(channel_id);
+
+        match message_type {
+            WsMessageType::Text => {
+                // Get the message from the blob
+                if let Ok(message) = String::from_utf8(blob.bytes.clone()) {
+                    verbose!(self, "Received WebSocket text message: {}", message);
+                    // Parse the message as JSON
+                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&message) {
+                        // Handle different message types
+                        if let Some(action) = json.get("action").and_then(|v| v.as_str()) {
+                            match action {
+                                "ping" => {
+                                    // App-level heartbeat from browsers; last_seen is already fresh
+                                    ws_ack(channel_id);
+                                }
+                                "get_tasks" => {
+                                    verbose!(self, "Getting tasks on channel {}", channel_id);
+                                    let offset = json
+                                        .get("offset")
+                                        .and_then(|v| v.as_u64())
+                                        .unwrap_or(0) as usize;
+                                    let limit = json
+                                        .get("limit")
+                                        .and_then(|v| v.as_u64())
+                                        .map(|v| v as usize);
+                                    let include_future = json
+                                        .get("all")
+                                        .and_then(|v| v.as_bool())
+                                        .unwrap_or(false);
+                                    ws_get_tasks(channel_id, self.channel_tasks(channel_id, include_future), offset, limit);
+                                }
+                                "get_task" => {
+                                    if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+                                        let result = self.find_task(id).cloned();
+                                        if let Err(e) = &result {
+                                            error!("{}", e);
+                                        }
+                                        ws_get_task(channel_id, result);
+                                    } else {
+                                        error!("get_task requires an id");
+                                        ws_error(channel_id, TodoErrorCode::InvalidId, "get_task requires an id");
+                                    }
+                                }
+                                "stream_tasks" => {
+                                    let chunk_size = json
+                                        .get("chunk_size")
+                                        .and_then(|v| v.as_u64())
+                                        .map(|v| (v as usize).clamp(1, MAX_STREAM_CHUNK))
+                                        .unwrap_or(DEFAULT_STREAM_CHUNK);
+                                    let include_future = json
+                                        .get("all")
+                                        .and_then(|v| v.as_bool())
+                                        .unwrap_or(false);
+                                    verbose!(self, "Streaming tasks on channel {} in chunks of {}", channel_id, chunk_size);
+                                    ws_stream_tasks(channel_id, &self.channel_tasks(channel_id, include_future), chunk_size);
+                                }
+                                "add_task" => {
+                                    if let Some(text) = json.get("text").and_then(|v| v.as_str()) {
+                                        let priority = json
+                                            .get("priority")
+                                            .and_then(|v| v.as_u64())
+                                            .map(|p| p.min(u8::MAX as u64) as u8);
+                                        let due_date = json.get("due_date").and_then(|v| v.as_u64());
+                                        match self.create_task(text, priority, due_date) {
+                                            Ok(new_task) => {
+                                                verbose!(self, "Adding task on channel {}", channel_id);
+                                                self.broadcast_task("task_added", &new_task);
+                                                self.notify_clients(vec![new_task]);
+                                            }
+                                            Err(e) => {
+                                                error!("{}", e);
+                                                ws_error(channel_id, e.code, &e.message);
+                                            }
+                                        }
+                                    } else {
+                                        error!("add_task requires a text");
+                                        ws_error(channel_id, TodoErrorCode::EmptyText, "add_task requires a text");
+                                    }
+                                }
+                                "toggle_task" => {
+                                    if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+                                        let toggled = self
+                                            .find_task(id)
+                                            .map(|t| !t.completed)
+                                            .and_then(|completed| self.set_completed(id, completed));
+                                        match toggled {
+                                            Ok((task, ancestors)) => {
+                                                self.broadcast_task("task_toggled", &task);
+                                                if task.completed {
+                                                    self.check_inbox_zero();
+                                                }
+                                                self.notify_clients([vec![task], ancestors].concat());
+                                            }
+                                            Err(e) => {
+                                                error!("{}", e);
+                                                ws_error(channel_id, e.code, &e.message);
+                                            }
+                                        }
+                                    } else {
+                                        error!("toggle_task requires an id");
+                                        ws_error(channel_id, TodoErrorCode::InvalidId, "toggle_task requires an id");
+                                    }
+                                }
+                                "delete_task" => {
+                                    if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+                                        match self.remove_task(id) {
+                                            Ok(removed) => {
+                                                verbose!(self, "Deleting task on channel {}", channel_id);
+                                                self.broadcast_task("task_deleted", &removed);
+                                            }
+                                            Err(e) => {
+                                                error!("{}", e);
+                                                ws_error(channel_id, e.code, &e.message);
+                                            }
+                                        }
+                                    } else {
+                                        error!("delete_task requires an id");
+                                        ws_error(channel_id, TodoErrorCode::InvalidId, "delete_task requires an id");
+                                    }
+                                }
+                                "reorder" => {
+                                    if let Some(ids) = json.get("ids").and_then(|v| v.as_array()) {
+                                        let ids: Vec<String> = ids
+                                            .iter()
+                                            .filter_map(|v| v.as_str().map(str::to_string))
+                                            .collect();
+                                        verbose!(self, "Reordering tasks on channel {}", channel_id);
+                                        self.reorder(&ids);
+                                        self.broadcast_tasks("tasks_overview");
+                                    } else {
+                                        error!("reorder requires an ids array");
+                                        ws_error(channel_id, TodoErrorCode::InvalidInput, "reorder requires an ids array");
+                                    }
+                                }
+                                "clear_completed" => {
+                                    verbose!(self, "Clearing completed tasks on channel {}", channel_id);
+                                    self.remove_completed();
+                                }
+                                "subscribe_filter" => {
+                                    // A missing or null filter goes back to all tasks
+                                    match json.get("filter").filter(|f| !f.is_null()) {
+                                        None => {
+                                            verbose!(self, "Clearing filter on channel {}", channel_id);
+                                            self.channel_filters.remove(&channel_id);
+                                        }
+                                        Some(filter) => {
+                                            match serde_json::from_value::<TaskFilter>(filter.clone()) {
+                                                Ok(filter) => {
+                                                    verbose!(self, "Setting filter on channel {}: {:?}", channel_id, filter);
+                                                    self.channel_filters.insert(channel_id, filter);
+                                                }
+                                                Err(e) => {
+                                                    let message = format!("Invalid filter: {}", e);
+                                                    error!("{}", message);
+                                                    ws_error(channel_id, TodoErrorCode::InvalidInput, &message);
+                                                    return;
+                                                }
+                                            }
                                         }
                                     }
+                                    ws_get_tasks(channel_id, self.channel_tasks(channel_id, false), 0, None);
                                 }
                                 _ => {
-                                    error!("Unknown WebSocket action: {}", action);
+                                    let message = format!("Unknown WebSocket action: {}", action);
+                                    error!("{}", message);
+                                    ws_error(channel_id, TodoErrorCode::InvalidInput, &message);
                                 }
                             }
+                        } else {
+                            error!("WebSocket message has no action");
+                            ws_error(channel_id, TodoErrorCode::InvalidInput, "Message has no action");
                         }
+                    } else {
+                        error!("WebSocket message is not valid JSON");
+                        ws_error(channel_id, TodoErrorCode::InvalidInput, "Message is not valid JSON");
                     }
+                } else {
+                    error!("WebSocket message is not valid UTF-8");
+                    ws_error(channel_id, TodoErrorCode::InvalidInput, "Message is not valid UTF-8");
                 }
             }
             WsMessageType::Binary => {
                 error!("Received WebSocket binary message");
+                ws_error(channel_id, TodoErrorCode::InvalidInput, "Binary messages are not supported");
             }
             WsMessageType::Ping => {
-                debug!("Received WebSocket ping message");
+                verbose!(self, "Received WebSocket ping message");
                 ws_ack(channel_id);
             }
             WsMessageType::Pong => {
-                debug!("Received WebSocket pong message");
+                verbose!(self, "Received WebSocket pong message");
                 ws_ack(channel_id);
             }
             WsMessageType::Close => {
-                debug!("Received WebSocket close message");
+                verbose!(self, "Received WebSocket close message");
                 let server = get_server().unwrap();
                 server.handle_websocket_close(channel_id);
                 self.ws_channels.remove(&channel_id);
+                self.last_seen.remove(&channel_id);
+                self.channel_filters.remove(&channel_id);
             }
         }
     }
@@ -371,8 +3007,9 @@ impl TodoState {
 // Access current state with &self (read) or &mut self (write)
 
 // 2. ERROR HANDLING
-// Return Result<T, String> for fallible operations
-// The String error will be sent to the frontend
+// Return Result<T, TodoError> for fallible operations
+// The error reaches the frontend as { "code": ..., "message": ... }
+// Remote handlers keep String errors so older peers still understand them
 
 // 3. FRONTEND COMMUNICATION
 // Frontend calls HTTP endpoints via POST to /api
@@ -8,7 +8,8 @@ import {
   ToggleTaskRequest,
   AddTaskResponse,
   GetTasksResponse,
-  ToggleTaskResponse
+  ToggleTaskResponse,
+  TodoError
 } from "./types/todo";
 
 const BASE_URL = import.meta.env.BASE_URL;
@@ -19,6 +20,13 @@ const PROXY_TARGET = `${(import.meta.env.VITE_NODE_URL || "http://localhost:8080
 // WebSocket URL for raw connection
 const WEBSOCKET_URL = `ws://localhost:8080${BASE_URL}/ws`;
 
+// Browsers can't send ping frames, so an idle tab sends a `ping` action to
+// stay under the server's 90s presence timeout
+const HEARTBEAT_INTERVAL_MS = 30_000;
+
+// Server frames that carry the channel's full task list in `tasks`
+const TASK_LIST_FRAMES = ["tasks_overview", "task_added", "task_toggled", "task_edited", "task_deleted"];
+
 console.log('BASE_URL:', BASE_URL);
 console.log('PROXY_TARGET:', PROXY_TARGET);
 console.log('WEBSOCKET_URL:', WEBSOCKET_URL);
@@ -28,6 +36,7 @@ function App() {
   const [nodeConnected, setNodeConnected] = useState(true);
   const [wsConnected, setWsConnected] = useState(false);
   const [newTaskText, setNewTaskText] = useState("");
+  const [wsError, setWsError] = useState<TodoError | null>(null);
   const wsRef = useRef<WebSocket | null>(null);
 
   // Send message via WebSocket
@@ -87,6 +96,12 @@ function App() {
       ws.send(JSON.stringify({ action: "get_tasks" }));
     };
 
+    const heartbeat = setInterval(() => {
+      if (ws.readyState === WebSocket.OPEN) {
+        ws.send(JSON.stringify({ action: "ping" }));
+      }
+    }, HEARTBEAT_INTERVAL_MS);
+
     ws.onmessage = (event) => {
       console.log('WebSocket message received:', event.data);
       try {
@@ -94,11 +109,15 @@ function App() {
         console.log("Parsed WebSocket message:", data);
         
         // Handle different message types
-        if (data.type === "tasks_overview" || data.type === "task_added" || data.type === "task_toggled") {
+        if (TASK_LIST_FRAMES.includes(data.type)) {
           if (data.tasks) {
             console.log("Updating tasks from WebSocket:", data.tasks);
             setTasks(data.tasks);
+            setWsError(null);
           }
+        } else if (data.type === "error") {
+          console.error("WebSocket error from server:", data.code, data.message);
+          setWsError({ code: data.code, message: data.message });
         }
       } catch (error) {
         console.error("Error parsing WebSocket message:", error);
@@ -118,6 +137,7 @@ function App() {
     // Cleanup
     return () => {
       console.log("Closing WebSocket connection");
+      clearInterval(heartbeat);
       if (ws.readyState === WebSocket.OPEN) {
         ws.close();
       }
@@ -143,6 +163,11 @@ function App() {
         </div>
       )}
       <h2>Todo List</h2>
+      {wsError && (
+        <p style={{ color: "red" }} onClick={() => setWsError(null)}>
+          {wsError.message}
+        </p>
+      )}
       <div className="card">
         <div className="input-row" style={{ marginBottom: '1em' }}>
           <input 
@@ -3,6 +3,17 @@ export interface TodoItem {
   id: string;
   text: string;
   completed: boolean;
+  completed_at?: number | null; // Unix seconds when marked done
+  icon?: string | null; // Optional emoji or short label
+  updated_at: number; // Unix seconds of the last change
+  priority: number; // 0 = low, 1 = medium (default), 2 = high
+  due_date?: number | null; // Unix seconds
+  start_date?: number | null; // Unix seconds; hidden from active views until then
+  tags: string[]; // Lowercase labels
+  shared: boolean; // Visible to peers; private tasks stay on this node
+  estimate_mins?: number | null; // Expected effort in minutes
+  parent_id?: string | null; // Set on subtasks
+  recurrence?: number | null; // Repeat interval in seconds
 }
 
 // Define the type for the state managed by the Zustand store
@@ -30,10 +41,18 @@ export interface ToggleTaskRequest {
 
 
 // --- Response Types ---
-// Generic response wrapper for Rust Result<T, E> where E is String
+// Error returned by handlers; branch on `code`, show `message`
+export type TodoErrorCode = "NotFound" | "EmptyText" | "InvalidId" | "Conflict" | "InvalidInput";
+
+export interface TodoError {
+  code: TodoErrorCode;
+  message: string;
+}
+
+// Generic response wrapper for Rust Result<T, TodoError>
 interface RustResponse<T> {
   Ok?: T;
-  Err?: string;
+  Err?: TodoError;
 }
 
 // Response type for the add_task endpoint